@@ -0,0 +1,109 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+extern crate relm_attributes;
+#[macro_use]
+extern crate relm_derive;
+
+use gtk::{
+    ButtonExt,
+    Inhibit,
+    OrientableExt,
+    WidgetExt,
+};
+use gtk::Orientation::Vertical;
+use relm::Widget;
+use relm::gtk_ext::ActionableExtManual;
+use relm_attributes::widget;
+
+use self::Msg::*;
+
+#[derive(Clone)]
+pub struct Model {
+    counter: i32,
+    saved: bool,
+}
+
+#[derive(Msg)]
+pub enum Msg {
+    Increment,
+    Quit,
+    Save,
+}
+
+#[widget]
+impl Widget for Win {
+    fn model() -> Model {
+        Model {
+            counter: 0,
+            saved: true,
+        }
+    }
+
+    fn update(&mut self, event: Msg, model: &mut Model) {
+        match event {
+            Increment => {
+                model.counter += 1;
+                model.saved = false;
+            },
+            Quit => gtk::main_quit(),
+            Save => {
+                println!("Saved {}", model.counter);
+                model.saved = true;
+            },
+        }
+    }
+
+    view! {
+        gtk::Window {
+            // Send Increment when Ctrl+I is pressed.
+            accel "<Primary>i" => Increment,
+            // Create the action win.save, which can be activated with Ctrl+S and is only enabled
+            // when the counter was not saved.
+            action "save"(enabled: !model.saved, accel: "<Primary>s") => Save,
+            gtk::Box {
+                orientation: Vertical,
+                gtk::Button {
+                    clicked => Increment,
+                    label: "+",
+                },
+                gtk::Label {
+                    text: &model.counter.to_string(),
+                },
+                // This button is insensitive while the action is disabled.
+                gtk::Button {
+                    action: "win.save",
+                    label: "Save",
+                },
+            },
+            delete_event(_, _) => (Quit, Inhibit(false)),
+        }
+    }
+}
+
+fn main() {
+    Win::run(()).unwrap();
+}
@@ -0,0 +1,90 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate gio;
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+extern crate relm_attributes;
+#[macro_use]
+extern crate relm_derive;
+
+use gio::{File, FileExt, APPLICATION_HANDLES_OPEN};
+use gtk::{OrientableExt, WidgetExt};
+use gtk::Orientation::Vertical;
+use relm::{ApplicationWidget, Widget};
+use relm_attributes::widget;
+
+use self::Msg::*;
+
+#[derive(Clone)]
+pub struct Model {
+    files: String,
+}
+
+#[derive(Msg)]
+pub enum Msg {
+    Open(Vec<String>),
+}
+
+#[widget]
+impl Widget for Win {
+    fn model() -> Model {
+        Model {
+            files: String::new(),
+        }
+    }
+
+    fn update(&mut self, event: Msg, model: &mut Model) {
+        match event {
+            Open(files) => model.files = files.join("\n"),
+        }
+    }
+
+    view! {
+        // The application quits when this window is closed.
+        gtk::Window {
+            gtk::Box {
+                orientation: Vertical,
+                gtk::Label {
+                    text: &model.files,
+                },
+            },
+        }
+    }
+}
+
+impl ApplicationWidget for Win {
+    fn open(files: Vec<File>, _hint: &str) -> Option<Msg> {
+        let paths = files.iter()
+            .filter_map(|file| file.get_path())
+            .map(|path| path.display().to_string())
+            .collect();
+        Some(Open(paths))
+    }
+}
+
+fn main() {
+    // Files passed on the command line are sent to Win::open().
+    relm::run_application::<Win>("com.github.antoyo.relm.application", APPLICATION_HANDLES_OPEN, ()).unwrap();
+}
@@ -48,7 +48,9 @@ pub enum Msg {
 
 // An alternative to the #[widget] attribute which works on stable.
 relm_widget! {
-    impl Widget for Win {
+    /// The main window.
+    // The visibility of the widget struct can be given before impl.
+    pub(crate) impl Widget for Win {
         // The initial model.
         fn model() -> Model {
             Model {
@@ -0,0 +1,148 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+extern crate relm_attributes;
+#[macro_use]
+extern crate relm_derive;
+
+use gtk::{
+    ButtonExt,
+    Inhibit,
+    LabelExt,
+    OrientableExt,
+    WidgetExt,
+};
+use gtk::Orientation::Vertical;
+use relm::Widget;
+use relm_attributes::widget;
+
+use self::CounterMsg::*;
+use self::Msg::*;
+
+#[derive(Clone)]
+pub struct CounterModel {
+    counter: i32,
+    step: i32,
+}
+
+#[derive(Msg)]
+pub enum CounterMsg {
+    Decrement,
+    Increment,
+    SetStep(i32),
+}
+
+fn step_label(sign: char, step: i32) -> String {
+    format!("{}{}", sign, step)
+}
+
+#[widget]
+impl Widget for Counter {
+    fn model() -> CounterModel {
+        CounterModel {
+            counter: 0,
+            step: 1,
+        }
+    }
+
+    fn update(&mut self, event: CounterMsg, model: &mut CounterModel) {
+        match event {
+            Decrement => model.counter -= model.step,
+            Increment => model.counter += model.step,
+            SetStep(step) => model.step = step,
+        }
+    }
+
+    // The parent sets the step property by sending the SetStep message, so that the model of the
+    // counter stays up to date.
+    props! {
+        step: i32 => SetStep,
+    }
+
+    view! {
+        gtk::Box {
+            orientation: Vertical,
+            gtk::Button {
+                clicked => Increment,
+                label: &step_label('+', model.step),
+            },
+            gtk::Label {
+                text: &model.counter.to_string(),
+            },
+            gtk::Button {
+                clicked => Decrement,
+                label: &step_label('-', model.step),
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Model {
+    step: i32,
+}
+
+#[derive(Msg)]
+pub enum Msg {
+    DoubleStep,
+    Quit,
+}
+
+#[widget]
+impl Widget for Win {
+    fn model() -> Model {
+        Model {
+            step: 1,
+        }
+    }
+
+    fn update(&mut self, event: Msg, model: &mut Model) {
+        match event {
+            DoubleStep => model.step *= 2,
+            Quit => gtk::main_quit(),
+        }
+    }
+
+    view! {
+        gtk::Window {
+            gtk::Box {
+                orientation: Vertical,
+                gtk::Button {
+                    clicked => DoubleStep,
+                    label: "Double the step",
+                },
+                Counter {
+                    step: model.step,
+                },
+            },
+            delete_event(_, _) => (Quit, Inhibit(false)),
+        }
+    }
+}
+
+fn main() {
+    Win::run(()).unwrap();
+}
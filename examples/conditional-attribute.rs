@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+extern crate relm_attributes;
+#[macro_use]
+extern crate relm_derive;
+
+use gtk::{
+    ButtonExt,
+    EntryExt,
+    Inhibit,
+    LabelExt,
+    OrientableExt,
+    WidgetExt,
+};
+use gtk::Orientation::Vertical;
+use relm::Widget;
+use relm_attributes::widget;
+
+use self::Msg::*;
+
+#[derive(Clone)]
+pub struct Model {
+    attempts: u32,
+    logged_in: bool,
+    user: String,
+}
+
+#[derive(Msg)]
+pub enum Msg {
+    Login,
+    Logout,
+    Quit,
+}
+
+#[widget]
+impl Widget for Win {
+    fn model() -> Model {
+        Model {
+            attempts: 0,
+            logged_in: false,
+            user: "relm".to_string(),
+        }
+    }
+
+    fn update(&mut self, event: Msg, model: &mut Model) {
+        match event {
+            Login => {
+                model.attempts += 1;
+                model.logged_in = true;
+            },
+            Logout => model.logged_in = false,
+            Quit => gtk::main_quit(),
+        }
+    }
+
+    view! {
+        gtk::Window {
+            gtk::Box {
+                orientation: Vertical,
+                // Only the widgets of the active branch exist: they are created and destroyed when
+                // the model fields used in the conditions change.
+                // A branch contains one widget, with its children, but it cannot contain another if,
+                // a for or a #[container].
+                if model.logged_in {
+                    gtk::Box {
+                        orientation: Vertical,
+                        gtk::Label {
+                            text: &format!("Welcome {}", model.user),
+                        },
+                        gtk::Button {
+                            clicked => Logout,
+                            label: "Log out",
+                        },
+                    }
+                }
+                else if model.attempts > 0 {
+                    gtk::Button {
+                        clicked => Login,
+                        label: "Log in again",
+                    }
+                }
+                else {
+                    gtk::Button {
+                        clicked => Login,
+                        label: "Log in",
+                    }
+                },
+                gtk::Entry {
+                    placeholder_text: "Always visible",
+                },
+            },
+            delete_event(_, _) => (Quit, Inhibit(false)),
+        }
+    }
+}
+
+fn main() {
+    Win::run(()).unwrap();
+}
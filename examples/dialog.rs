@@ -0,0 +1,157 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+extern crate relm_attributes;
+#[macro_use]
+extern crate relm_derive;
+
+use gtk::{
+    Button,
+    ButtonExt,
+    ContainerExt,
+    Inhibit,
+    Label,
+    OrientableExt,
+    WidgetExt,
+    Window,
+    WindowType,
+};
+use gtk::Orientation::{Horizontal, Vertical};
+use relm::{DialogWidget, RemoteRelm, Widget};
+use relm_attributes::widget;
+
+use self::ConfirmMsg::*;
+use self::Msg::*;
+
+#[derive(Msg)]
+pub enum ConfirmMsg {
+    No,
+    Yes,
+}
+
+#[widget]
+impl Widget for Confirm {
+    fn model(question: String) -> String {
+        question
+    }
+
+    fn update(&mut self, _event: ConfirmMsg, _model: &mut String) {
+    }
+
+    view! {
+        gtk::Window {
+            title: "Confirmation",
+            gtk::Box {
+                orientation: Vertical,
+                gtk::Label {
+                    text: &model,
+                },
+                gtk::Box {
+                    orientation: Horizontal,
+                    gtk::Button {
+                        clicked => No,
+                        label: "No",
+                    },
+                    gtk::Button {
+                        clicked => Yes,
+                        label: "Yes",
+                    },
+                },
+            },
+        }
+    }
+}
+
+// The dialog is closed with a response when one of its buttons is clicked.
+impl DialogWidget for Confirm {
+    type Response = bool;
+
+    fn response(&self, msg: ConfirmMsg, _model: &String) -> Option<bool> {
+        match msg {
+            No => Some(false),
+            Yes => Some(true),
+        }
+    }
+}
+
+#[derive(Msg)]
+enum Msg {
+    ConfirmQuit,
+    Quit,
+}
+
+#[derive(Clone)]
+struct Win {
+    relm: RemoteRelm<Win>,
+    window: Window,
+}
+
+impl Widget for Win {
+    type Model = ();
+    type ModelParam = ();
+    type Msg = Msg;
+    type Root = Window;
+
+    fn model(_: ()) -> () {
+        ()
+    }
+
+    fn root(&self) -> &Self::Root {
+        &self.window
+    }
+
+    fn update(&mut self, event: Msg, _model: &mut ()) {
+        match event {
+            // The dialog does not block this function: Quit is sent later, if the user confirms.
+            ConfirmQuit => relm::dialog::<Confirm, _, _, _>(&self.relm, Some(&self.window),
+                "Do you really want to quit?".to_string(), |quit| if quit { Some(Quit) } else { None }),
+            Quit => gtk::main_quit(),
+        }
+    }
+
+    fn view(relm: &RemoteRelm<Self>, _model: &()) -> Self {
+        let window = Window::new(WindowType::Toplevel);
+        let vbox = gtk::Box::new(Vertical, 0);
+        let label = Label::new(Some("Close this window to open the confirmation dialog."));
+        vbox.add(&label);
+        let button = Button::new_with_label("Quit");
+        vbox.add(&button);
+        window.add(&vbox);
+        window.show_all();
+
+        connect!(relm, button, connect_clicked(_), ConfirmQuit);
+        connect!(relm, window, connect_delete_event(_, _) (Some(ConfirmQuit), Inhibit(true)));
+
+        Win {
+            relm: relm.clone(),
+            window: window,
+        }
+    }
+}
+
+fn main() {
+    Win::run(()).unwrap();
+}
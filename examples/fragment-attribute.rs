@@ -0,0 +1,121 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+extern crate relm_attributes;
+#[macro_use]
+extern crate relm_derive;
+
+use gtk::{
+    ButtonExt,
+    EditableSignals,
+    EntryExt,
+    Inhibit,
+    LabelExt,
+    OrientableExt,
+    WidgetExt,
+};
+use gtk::Orientation::{Horizontal, Vertical};
+use relm::Widget;
+use relm_attributes::widget;
+
+use self::Msg::*;
+
+#[derive(Clone)]
+pub struct Model {
+    first_name: String,
+    last_name: String,
+}
+
+#[derive(Msg)]
+pub enum Msg {
+    Changed,
+    Clear,
+    Quit,
+}
+
+fn full_name(first_name: &str, last_name: &str) -> String {
+    format!("{} {}", first_name, last_name)
+}
+
+#[widget]
+impl Widget for Win {
+    fn model() -> Model {
+        Model {
+            first_name: String::new(),
+            last_name: String::new(),
+        }
+    }
+
+    fn update(&mut self, event: Msg, model: &mut Model) {
+        match event {
+            // The label is set since it uses the fields updated by the bound entries.
+            Changed => (),
+            Clear => {
+                model.first_name.clear();
+                model.last_name.clear();
+            },
+            Quit => gtk::main_quit(),
+        }
+    }
+
+    // A fragment is inlined in the view where it is used, with its parameters replaced by the
+    // arguments.
+    fragment! {
+        field($label, $value) {
+            gtk::Box {
+                orientation: Horizontal,
+                gtk::Label {
+                    text: $label,
+                },
+                gtk::Entry {
+                    text: bind $value => Changed,
+                },
+            }
+        }
+    }
+
+    view! {
+        gtk::Window {
+            gtk::Box {
+                orientation: Vertical,
+                field!("First name", model.first_name),
+                field!("Last name", model.last_name),
+                gtk::Label {
+                    text: &full_name(&model.first_name, &model.last_name),
+                },
+                gtk::Button {
+                    clicked => Clear,
+                    label: "Clear",
+                },
+            },
+            delete_event(_, _) => (Quit, Inhibit(false)),
+        }
+    }
+}
+
+fn main() {
+    Win::run(()).unwrap();
+}
@@ -0,0 +1,115 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+extern crate relm_attributes;
+#[macro_use]
+extern crate relm_derive;
+
+use gtk::{
+    ButtonExt,
+    Inhibit,
+    LabelExt,
+    WidgetExt,
+};
+use relm::Widget;
+use relm::gtk_ext::{GridExtManual, NotebookExtManual};
+use relm_attributes::widget;
+
+use self::Msg::*;
+
+#[derive(Clone)]
+pub struct Model {
+    column: i32,
+    row: i32,
+}
+
+#[derive(Msg)]
+pub enum Msg {
+    Move,
+    Quit,
+}
+
+#[widget]
+impl Widget for Win {
+    fn model() -> Model {
+        Model {
+            column: 0,
+            row: 0,
+        }
+    }
+
+    fn update(&mut self, event: Msg, model: &mut Model) {
+        match event {
+            Move => {
+                model.column = (model.column + 1) % 3;
+                if model.column == 0 {
+                    model.row = (model.row + 1) % 3;
+                }
+            },
+            Quit => gtk::main_quit(),
+        }
+    }
+
+    view! {
+        gtk::Window {
+            gtk::Notebook {
+                gtk::Grid {
+                    packing: {
+                        tab_label: "Grid",
+                    },
+                    gtk::Button {
+                        clicked => Move,
+                        label: "Move",
+                        // The button is moved in the grid when the model changes.
+                        packing: {
+                            left_attach: model.column,
+                            top_attach: model.row,
+                        },
+                    },
+                    gtk::Label {
+                        text: "Corner",
+                        packing: {
+                            left_attach: 3,
+                            top_attach: 3,
+                        },
+                    },
+                },
+                gtk::Label {
+                    text: "Another page",
+                    packing: {
+                        reorderable: true,
+                        tab_label: "Label",
+                    },
+                },
+            },
+            delete_event(_, _) => (Quit, Inhibit(false)),
+        }
+    }
+}
+
+fn main() {
+    Win::run(()).unwrap();
+}
@@ -0,0 +1,177 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+extern crate relm_attributes;
+#[macro_use]
+extern crate relm_derive;
+
+use gtk::{
+    ButtonExt,
+    Inhibit,
+    LabelExt,
+    OrientableExt,
+    WidgetExt,
+};
+use gtk::Orientation::Vertical;
+use relm::{ListItem, Widget};
+use relm_attributes::widget;
+
+use self::Msg::*;
+use self::RowMsg::*;
+
+#[derive(Clone, PartialEq)]
+pub struct Task {
+    id: u32,
+    text: String,
+}
+
+#[derive(Clone)]
+pub struct RowModel {
+    task: Task,
+}
+
+#[derive(Msg)]
+pub enum RowMsg {
+    Delete,
+    SetTask(Task),
+}
+
+#[widget]
+impl Widget for Row {
+    fn model(task: Task) -> RowModel {
+        RowModel {
+            task: task,
+        }
+    }
+
+    fn update(&mut self, event: RowMsg, model: &mut RowModel) {
+        match event {
+            // To be listened by the user.
+            Delete => (),
+            SetTask(task) => model.task = task,
+        }
+    }
+
+    view! {
+        gtk::Box {
+            gtk::Label {
+                text: &model.task.text,
+            },
+            gtk::Button {
+                clicked => Delete,
+                label: "Delete",
+            },
+        }
+    }
+}
+
+// Rows are kept when their task changes: they receive the new task in a message instead.
+impl ListItem for Row {
+    fn changed(task: Task) -> Option<RowMsg> {
+        Some(SetTask(task))
+    }
+}
+
+#[derive(Clone)]
+pub struct Model {
+    next_id: u32,
+    tasks: Vec<Task>,
+}
+
+#[derive(Msg)]
+pub enum Msg {
+    Add,
+    Quit,
+    Remove(u32),
+    Reverse,
+    Shout,
+}
+
+#[widget]
+impl Widget for Win {
+    fn model() -> Model {
+        Model {
+            next_id: 0,
+            tasks: vec![],
+        }
+    }
+
+    fn update(&mut self, event: Msg, model: &mut Model) {
+        match event {
+            Add => {
+                let task = Task {
+                    id: model.next_id,
+                    text: format!("Task #{}", model.next_id),
+                };
+                model.next_id += 1;
+                model.tasks.push(task);
+            },
+            Quit => gtk::main_quit(),
+            Remove(id) => model.tasks.retain(|task| task.id != id),
+            Reverse => model.tasks.reverse(),
+            Shout => {
+                // Borrowing model.tasks mutably marks it as changed.
+                for task in &mut model.tasks {
+                    task.text = task.text.to_uppercase();
+                }
+            },
+        }
+    }
+
+    view! {
+        gtk::Window {
+            gtk::Box {
+                orientation: Vertical,
+                gtk::Box {
+                    gtk::Button {
+                        clicked => Add,
+                        label: "Add",
+                    },
+                    gtk::Button {
+                        clicked => Reverse,
+                        label: "Reverse",
+                    },
+                    gtk::Button {
+                        clicked => Shout,
+                        label: "Shout",
+                    },
+                },
+                // One Row is created for every task: the rows are matched with the tasks by id
+                // when the tasks change, so they are only created, removed or moved when needed.
+                for task in model.tasks, key = task.id {
+                    Row(task.clone()) {
+                        Delete => Remove(task.id),
+                    }
+                },
+            },
+            delete_event(_, _) => (Quit, Inhibit(false)),
+        }
+    }
+}
+
+fn main() {
+    Win::run(()).unwrap();
+}
@@ -0,0 +1,126 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+extern crate relm_attributes;
+#[macro_use]
+extern crate relm_derive;
+
+use gtk::{
+    ButtonExt,
+    Inhibit,
+    MenuItemExt,
+    OrientableExt,
+    WidgetExt,
+};
+use gtk::Orientation::Vertical;
+use relm::Widget;
+use relm_attributes::widget;
+
+use self::Msg::*;
+
+#[derive(Clone)]
+pub struct Model {
+    counter: i32,
+    recent: String,
+}
+
+#[derive(Msg)]
+pub enum Msg {
+    Decrement,
+    Increment,
+    Open,
+    Quit,
+}
+
+#[widget]
+impl Widget for Win {
+    fn model() -> Model {
+        Model {
+            counter: 0,
+            recent: "No recent file".to_string(),
+        }
+    }
+
+    fn update(&mut self, event: Msg, model: &mut Model) {
+        match event {
+            Decrement => model.counter -= 1,
+            Increment => model.counter += 1,
+            Open => model.recent = format!("File {}", model.counter),
+            Quit => gtk::main_quit(),
+        }
+    }
+
+    view! {
+        gtk::Window {
+            gtk::Box {
+                orientation: Vertical,
+                gtk::MenuBar {
+                    submenu "File" {
+                        item "Open" => Open,
+                        submenu "Recent" {
+                            // The label of this item is updated when model.recent changes.
+                            item(label: &model.recent) => Open,
+                        },
+                        separator,
+                        item "Quit" => Quit,
+                    },
+                    submenu "Counter" {
+                        item "Increment" => Increment,
+                        // This item is insensitive when the counter is 0.
+                        item "Decrement"(sensitive: model.counter > 0) => Decrement,
+                    },
+                },
+                gtk::Label {
+                    text: &model.counter.to_string(),
+                },
+                gtk::MenuButton {
+                    label: "Menu",
+                    // The menu of a MenuButton pops up when it is clicked.
+                    menu {
+                        item "Increment" => Increment,
+                        item "Decrement"(sensitive: model.counter > 0) => Decrement,
+                    },
+                },
+                gtk::Button {
+                    clicked => Increment,
+                    label: "+",
+                    // Show this popover next to the button when the counter is 10.
+                    gtk::Popover {
+                        visible: model.counter == 10,
+                        gtk::Label {
+                            text: "You reached 10",
+                        },
+                    },
+                },
+            },
+            delete_event(_, _) => (Quit, Inhibit(false)),
+        }
+    }
+}
+
+fn main() {
+    Win::run(()).unwrap();
+}
@@ -0,0 +1,140 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+#[macro_use]
+extern crate relm_derive;
+
+use gtk::{
+    Button,
+    ButtonExt,
+    ContainerExt,
+    Label,
+    WidgetExt,
+    Window,
+    WindowType,
+};
+use gtk::Orientation::Vertical;
+use relm::{RemoteRelm, Widget, WindowId};
+
+use self::Msg::*;
+
+#[derive(Clone)]
+struct Document {
+    label: Label,
+}
+
+impl Widget for Document {
+    type Model = String;
+    type ModelParam = String;
+    type Msg = ();
+    type Root = Label;
+
+    fn model(title: String) -> String {
+        title
+    }
+
+    fn root(&self) -> &Self::Root {
+        &self.label
+    }
+
+    fn update(&mut self, _event: (), _model: &mut String) {
+    }
+
+    // The root is not a gtk::Window, so open_window() will add it to a new window.
+    fn view(_relm: &RemoteRelm<Self>, model: &String) -> Self {
+        let label = Label::new(Some(model.as_str()));
+        label.show();
+        Document {
+            label: label,
+        }
+    }
+}
+
+#[derive(Msg)]
+enum Msg {
+    Closed(WindowId),
+    New,
+}
+
+#[derive(Clone)]
+struct Win {
+    document_count: usize,
+    label: Label,
+    relm: RemoteRelm<Win>,
+    window: Window,
+}
+
+impl Widget for Win {
+    type Model = ();
+    type ModelParam = ();
+    type Msg = Msg;
+    type Root = Window;
+
+    fn model(_: ()) -> () {
+        ()
+    }
+
+    fn root(&self) -> &Self::Root {
+        &self.window
+    }
+
+    fn update(&mut self, event: Msg, _model: &mut ()) {
+        match event {
+            Closed(id) => self.label.set_text(&format!("Document {:?} closed", id)),
+            New => {
+                self.document_count += 1;
+                let title = format!("Document {}", self.document_count);
+                // The main loop, started by run_windows(), is exited when the last window is closed,
+                // so no Quit message is needed.
+                relm::open_window::<Document, _, _>(&self.relm, title, Closed);
+            },
+        }
+    }
+
+    fn view(relm: &RemoteRelm<Self>, _model: &()) -> Self {
+        let window = Window::new(WindowType::Toplevel);
+
+        let vbox = gtk::Box::new(Vertical, 0);
+        let new_button = Button::new_with_label("New document");
+        vbox.add(&new_button);
+        let label = Label::new(None);
+        vbox.add(&label);
+        window.add(&vbox);
+
+        window.show_all();
+
+        connect!(relm, new_button, connect_clicked(_), New);
+
+        Win {
+            document_count: 0,
+            label: label,
+            relm: relm.clone(),
+            window: window,
+        }
+    }
+}
+
+fn main() {
+    relm::run_windows::<Win>(()).unwrap();
+}
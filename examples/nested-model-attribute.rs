@@ -0,0 +1,129 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+extern crate relm_attributes;
+#[macro_use]
+extern crate relm_derive;
+
+use gtk::{
+    ButtonExt,
+    Inhibit,
+    OrientableExt,
+    WidgetExt,
+};
+use gtk::Orientation::Vertical;
+use relm::Widget;
+use relm_attributes::widget;
+
+use self::Msg::*;
+
+#[derive(Clone, Default)]
+pub struct User {
+    age: u32,
+    name: String,
+}
+
+#[derive(Clone, Default)]
+pub struct Model {
+    clicks: u32,
+    user: User,
+}
+
+#[derive(Msg)]
+pub enum Msg {
+    Birthday,
+    ChangeUser,
+    Quit,
+    Reset,
+}
+
+#[widget]
+impl Widget for Win {
+    fn model() -> Model {
+        Model {
+            clicks: 0,
+            user: User {
+                age: 30,
+                name: "Alice".to_string(),
+            },
+        }
+    }
+
+    fn update(&mut self, event: Msg, model: &mut Model) {
+        match event {
+            // Only the label bound to model.user.age is updated.
+            Birthday => {
+                model.clicks += 1;
+                model.user.age += 1;
+            },
+            // Both labels bound to a field of model.user are updated.
+            ChangeUser => {
+                model.clicks += 1;
+                model.user = User {
+                    age: 25,
+                    name: "Bob".to_string(),
+                };
+            },
+            Quit => gtk::main_quit(),
+            // Every label is updated.
+            Reset => *model = Model::default(),
+        }
+    }
+
+    view! {
+        gtk::Window {
+            gtk::Box {
+                orientation: Vertical,
+                gtk::Label {
+                    text: &model.user.name,
+                },
+                gtk::Label {
+                    text: &model.user.age.to_string(),
+                },
+                gtk::Label {
+                    text: &model.clicks.to_string(),
+                },
+                gtk::Button {
+                    clicked => Birthday,
+                    label: "Birthday",
+                },
+                gtk::Button {
+                    clicked => ChangeUser,
+                    label: "Change user",
+                },
+                gtk::Button {
+                    clicked => Reset,
+                    label: "Reset",
+                },
+            },
+            delete_event(_, _) => (Quit, Inhibit(false)),
+        }
+    }
+}
+
+fn main() {
+    Win::run(()).unwrap();
+}
@@ -0,0 +1,144 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+extern crate relm_attributes;
+#[macro_use]
+extern crate relm_derive;
+
+use gtk::{
+    Inhibit,
+    LabelExt,
+    OrientableExt,
+    WidgetExt,
+};
+use gtk::Orientation::Vertical;
+use relm::{Placement, Position, Widget};
+use relm_attributes::widget;
+
+use self::Msg::*;
+
+#[derive(Msg)]
+pub enum TileMsg {
+}
+
+#[derive(Clone)]
+pub struct TileModel {
+    text: String,
+}
+
+#[widget]
+impl Widget for Tile {
+    fn model((left, top): (i32, i32)) -> TileModel {
+        TileModel {
+            text: format!("{}, {}", left, top),
+        }
+    }
+
+    // The container receiving the tile and its position are computed from the model parameter.
+    fn placement(&(left, top): &(i32, i32)) -> Placement {
+        if left < 0 || top < 0 {
+            // The tiles outside of the grid go at the start of the status bar.
+            Placement {
+                parent_id: Some("status".to_string()),
+                position: Some(Position::Index(0)),
+            }
+        }
+        else {
+            Placement {
+                parent_id: None,
+                position: Some(Position::Cell { left, top, width: 1, height: 1 }),
+            }
+        }
+    }
+
+    fn update(&mut self, _msg: TileMsg, _model: &mut TileModel) {
+    }
+
+    view! {
+        gtk::Label {
+            text: &model.text,
+        }
+    }
+}
+
+#[derive(Msg)]
+pub enum BoardMsg {
+}
+
+#[widget]
+impl Widget for Board {
+    fn model() -> () {
+    }
+
+    fn update(&mut self, _msg: BoardMsg, _model: &mut ()) {
+    }
+
+    view! {
+        gtk::Box {
+            orientation: Vertical,
+            #[container]
+            gtk::Grid {
+            },
+            #[container="status"]
+            gtk::Box {
+            },
+        }
+    }
+}
+
+#[derive(Msg)]
+pub enum Msg {
+    Quit,
+}
+
+#[widget]
+impl Widget for Win {
+    fn model() -> () {
+    }
+
+    fn update(&mut self, event: Msg, _model: &mut ()) {
+        match event {
+            Quit => gtk::main_quit(),
+        }
+    }
+
+    view! {
+        gtk::Window {
+            Board {
+                Tile(2, 2),
+                Tile(0, 0),
+                Tile(-1, 0),
+                Tile(1, 1),
+                Tile(0, -1),
+            },
+            delete_event(_, _) => (Quit, Inhibit(false)),
+        }
+    }
+}
+
+fn main() {
+    Win::run(()).unwrap();
+}
@@ -0,0 +1,159 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+extern crate relm_attributes;
+#[macro_use]
+extern crate relm_derive;
+
+use gtk::{
+    ButtonExt,
+    Inhibit,
+    LabelExt,
+    OrientableExt,
+    ToggleButtonExt,
+    WidgetExt,
+};
+use gtk::Orientation::Vertical;
+use relm::{RemoteRelm, Widget};
+use relm_attributes::widget;
+
+use self::Msg::*;
+use self::PanelMsg::*;
+
+#[derive(Clone)]
+pub struct PanelModel {
+    visible: bool,
+}
+
+#[derive(Msg)]
+pub enum PanelMsg {
+    CloseRequested,
+    Hide,
+}
+
+// Ask the parent whether the panel can be closed.
+fn ask_close(relm: &RemoteRelm<Panel>) -> (Option<PanelMsg>, ()) {
+    let can_close = relm.stream().emit_with_reply(CloseRequested).unwrap_or(true);
+    if can_close {
+        (Some(Hide), ())
+    }
+    else {
+        (None, ())
+    }
+}
+
+#[widget]
+impl Widget for Panel {
+    fn model() -> PanelModel {
+        PanelModel {
+            visible: true,
+        }
+    }
+
+    fn update(&mut self, event: PanelMsg, model: &mut PanelModel) {
+        match event {
+            CloseRequested => (),
+            Hide => model.visible = false,
+        }
+    }
+
+    view! {
+        gtk::Box {
+            orientation: Vertical,
+            visible: model.visible,
+            gtk::Label {
+                text: "Panel",
+            },
+            gtk::Button {
+                clicked => return ask_close(&relm),
+                label: "Close",
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Model {
+    locked: bool,
+    refusals: u32,
+}
+
+#[derive(Msg)]
+pub enum Msg {
+    Quit,
+    Refused,
+}
+
+// Answer to the panel: it can only be closed when it is not locked.
+fn close_panel(model: &mut Model) -> (Option<Msg>, bool) {
+    if model.locked {
+        (Some(Refused), false)
+    }
+    else {
+        (None, true)
+    }
+}
+
+#[widget]
+impl Widget for Win {
+    fn model() -> Model {
+        Model {
+            locked: true,
+            refusals: 0,
+        }
+    }
+
+    fn update(&mut self, event: Msg, model: &mut Model) {
+        match event {
+            Quit => gtk::main_quit(),
+            Refused => model.refusals += 1,
+        }
+    }
+
+    view! {
+        gtk::Window {
+            gtk::Box {
+                orientation: Vertical,
+                gtk::CheckButton {
+                    active: bind model.locked,
+                    label: "Locked",
+                },
+                Panel {
+                    // The value returned here is the reply received by the panel.
+                    CloseRequested with model => return close_panel(model),
+                },
+                gtk::Label {
+                    text: &model.refusals.to_string(),
+                },
+            },
+            delete_event(_, _) => (Quit, Inhibit(false)),
+        }
+    }
+}
+
+fn main() {
+    Win::run(()).unwrap();
+}
@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate gtk;
+#[macro_use]
+extern crate relm;
+extern crate relm_attributes;
+#[macro_use]
+extern crate relm_derive;
+
+use gtk::{
+    ButtonExt,
+    EditableSignals,
+    EntryExt,
+    Inhibit,
+    LabelExt,
+    OrientableExt,
+    SwitchExt,
+    ToggleButtonExt,
+    WidgetExt,
+};
+use gtk::Orientation::Vertical;
+use relm::Widget;
+use relm_attributes::widget;
+
+use self::Msg::*;
+
+#[derive(Clone)]
+pub struct Model {
+    name: String,
+    notifications: bool,
+    uppercase: bool,
+}
+
+#[derive(Msg)]
+pub enum Msg {
+    Changed,
+    Clear,
+    Quit,
+}
+
+fn format_name(name: &str, uppercase: bool) -> String {
+    if uppercase {
+        name.to_uppercase()
+    }
+    else {
+        name.to_string()
+    }
+}
+
+#[widget]
+impl Widget for Win {
+    fn model() -> Model {
+        Model {
+            name: String::new(),
+            notifications: true,
+            uppercase: false,
+        }
+    }
+
+    fn update(&mut self, event: Msg, model: &mut Model) {
+        match event {
+            // The model was already updated by the bound widgets: the label is set since it uses the
+            // fields bound to the widgets emitting this message.
+            Changed => (),
+            // The entry is cleared since its text is bound to model.name.
+            Clear => model.name.clear(),
+            Quit => gtk::main_quit(),
+        }
+    }
+
+    view! {
+        gtk::Window {
+            gtk::Box {
+                orientation: Vertical,
+                gtk::Entry {
+                    // Update model.name when the text is changed, then emit Changed.
+                    text: bind model.name => Changed,
+                },
+                gtk::Label {
+                    text: &format_name(&model.name, model.uppercase),
+                },
+                gtk::CheckButton {
+                    label: "Uppercase",
+                    active: bind model.uppercase => Changed,
+                },
+                gtk::Switch {
+                    // Only update model.notifications.
+                    active: bind model.notifications,
+                },
+                gtk::Button {
+                    clicked => Clear,
+                    label: "Clear",
+                },
+            },
+            delete_event(_, _) => (Quit, Inhibit(false)),
+        }
+    }
+}
+
+fn main() {
+    Win::run(()).unwrap();
+}
@@ -24,13 +24,14 @@ extern crate glib_itc;
 extern crate gtk;
 extern crate tokio_core;
 
+use std::any::{Any, TypeId};
 use std::collections::VecDeque;
 use std::io::Error;
 use std::sync::{Arc, Mutex};
 use std::sync::mpsc::channel;
 use std::thread;
 
-use futures::{Async, Poll, Stream};
+use futures::{Async, Future, Poll, Stream};
 use futures::task::{self, Task};
 use glib_itc::Sender;
 use tokio_core::reactor;
@@ -54,8 +55,13 @@ impl Core {
 }
 
 struct _EventStream<MSG> {
+    // The tasks of the futures waiting for the stream to be closed.
+    close_tasks: Vec<Arc<Mutex<Option<Task>>>>,
     events: VecDeque<MSG>,
-    observers: Vec<Box<Fn(MSG) + Send>>,
+    // The observers are shared so that emit() can call a copy of the list without holding the lock.
+    observers: Vec<Arc<Fn(MSG) + Send + Sync>>,
+    // Observers answering to the message, with the type of their reply.
+    reply_observers: Vec<(TypeId, Arc<Fn(MSG) -> Option<Box<Any>> + Send + Sync>)>,
     sender: Arc<Mutex<Sender>>,
     task: Option<Task>,
     terminated: bool,
@@ -71,8 +77,10 @@ impl<MSG> EventStream<MSG> {
     pub fn new(sender: Arc<Mutex<Sender>>) -> Self {
         EventStream {
             stream: Arc::new(Mutex::new(_EventStream {
+                close_tasks: vec![],
                 events: VecDeque::new(),
                 observers: vec![],
+                reply_observers: vec![],
                 sender: sender,
                 task: None,
                 terminated: false,
@@ -85,25 +93,81 @@ impl<MSG> EventStream<MSG> {
         let mut stream = self.stream.lock().unwrap();
         stream.sender.lock().unwrap().close()?;
         stream.terminated = true;
+        // The observers are dropped, so that they do not keep the streams of other widgets alive.
+        stream.observers.clear();
+        stream.reply_observers.clear();
         if let Some(ref task) = stream.task {
             task.unpark();
         }
+        for task in &stream.close_tasks {
+            if let Some(ref task) = *task.lock().unwrap() {
+                task.unpark();
+            }
+        }
         Ok(())
     }
 
+    /// Get a future resolved when the stream is closed.
+    /// It is used to cancel the futures of a widget when it is removed.
+    pub fn closed(&self) -> Closed<MSG> {
+        let task = Arc::new(Mutex::new(None));
+        let mut stream = self.stream.lock().unwrap();
+        // Forget the futures which were dropped.
+        stream.close_tasks.retain(|task| Arc::strong_count(task) > 1);
+        stream.close_tasks.push(task.clone());
+        Closed {
+            stream: self.stream.clone(),
+            task: task,
+        }
+    }
+
     pub fn emit(&self, event: MSG)
         where MSG: Clone + 'static
     {
-        let mut stream = self.stream.lock().unwrap();
-        if let Some(ref task) = stream.task {
-            task.unpark();
-        }
-        // TODO: try to avoid clone by sending a reference.
-        stream.events.push_back(event.clone());
+        self.emit_event(event, None);
+    }
+
+    fn emit_event(&self, event: MSG, reply_type: Option<TypeId>) -> Option<Box<Any>>
+        where MSG: Clone + 'static
+    {
+        // The observers are called without holding the lock, because they can emit to this stream,
+        // like a reply handler answering its child. A copy of the list is called, so that such an emit
+        // still finds every observer.
+        let (observers, reply_observers) = {
+            let mut stream = self.stream.lock().unwrap();
+            if let Some(ref task) = stream.task {
+                task.unpark();
+            }
+            // TODO: try to avoid clone by sending a reference.
+            stream.events.push_back(event.clone());
+            (stream.observers.clone(), stream.reply_observers.clone())
+        };
 
-        for observer in &stream.observers {
+        for observer in &observers {
             observer(event.clone());
         }
+
+        // Every reply observer is called, but only the first reply of the requested type is kept.
+        let mut result = None;
+        for &(observer_reply_type, ref observer) in &reply_observers {
+            let reply = observer(event.clone());
+            if result.is_none() && Some(observer_reply_type) == reply_type {
+                result = reply;
+            }
+        }
+
+        result
+    }
+
+    /// Emit the event and return the reply of the observers added with `observe_with_reply()`.
+    /// `None` is returned when no observer replies, like a `with model` handler of the parent when the
+    /// parent emits the event from its `update()`.
+    pub fn emit_with_reply<REPLY: 'static>(&self, event: MSG) -> Option<REPLY>
+        where MSG: Clone + 'static
+    {
+        self.emit_event(event, Some(TypeId::of::<REPLY>()))
+            .and_then(|reply| reply.downcast::<REPLY>().ok())
+            .map(|reply| *reply)
     }
 
     fn get_event(&self) -> Option<MSG> {
@@ -115,8 +179,17 @@ impl<MSG> EventStream<MSG> {
         stream.terminated
     }
 
-    pub fn observe<CALLBACK: Fn(MSG) + Send + 'static>(&self, callback: CALLBACK) {
-        self.stream.lock().unwrap().observers.push(Box::new(callback));
+    pub fn observe<CALLBACK: Fn(MSG) + Send + Sync + 'static>(&self, callback: CALLBACK) {
+        self.stream.lock().unwrap().observers.push(Arc::new(callback));
+    }
+
+    pub fn observe_with_reply<REPLY, CALLBACK>(&self, callback: CALLBACK)
+        where MSG: 'static,
+              REPLY: 'static,
+              CALLBACK: Fn(MSG) -> Option<REPLY> + Send + Sync + 'static,
+    {
+        let observer = move |event| callback(event).map(|reply| Box::new(reply) as Box<Any>);
+        self.stream.lock().unwrap().reply_observers.push((TypeId::of::<REPLY>(), Arc::new(observer)));
     }
 
     pub fn pop_ui_events(&self) -> Option<MSG> {
@@ -124,6 +197,29 @@ impl<MSG> EventStream<MSG> {
     }
 }
 
+/// Future resolved when an `EventStream` is closed.
+pub struct Closed<MSG> {
+    stream: Arc<Mutex<_EventStream<MSG>>>,
+    task: Arc<Mutex<Option<Task>>>,
+}
+
+impl<MSG> Future for Closed<MSG> {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        // The stream is locked until the task is saved, so that close() cannot be missed.
+        let stream = self.stream.lock().unwrap();
+        if stream.terminated {
+            Ok(Async::Ready(()))
+        }
+        else {
+            *self.task.lock().unwrap() = Some(task::park());
+            Ok(Async::NotReady)
+        }
+    }
+}
+
 impl<MSG: Clone + 'static> Stream for EventStream<MSG> {
     type Item = MSG;
     type Error = ();
@@ -102,8 +102,9 @@ fn impl_msg(ast: &MacroInput) -> Tokens {
 fn derive_clone(ast: &MacroInput) -> Tokens {
     let generics = &ast.generics;
     let name = &ast.ident;
+    let (_, ty_generics, _) = generics.split_for_impl();
     let typ = quote! {
-        #name #generics
+        #name #ty_generics
     };
 
     match ast.body {
@@ -114,6 +115,7 @@ fn derive_clone(ast: &MacroInput) -> Tokens {
 }
 
 fn derive_clone_enum(name: &Ident, typ: Tokens, generics: &Generics, variants: &[Variant]) -> Tokens {
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
     let variant_idents_values: Vec<_> = variants.iter().map(|variant| {
         let has_value =
             if let VariantData::Tuple(_) = variant.data {
@@ -150,7 +152,7 @@ fn derive_clone_enum(name: &Ident, typ: Tokens, generics: &Generics, variants: &
     });
 
     quote! {
-        impl #generics Clone for #typ {
+        impl #impl_generics Clone for #typ #where_clause {
             fn clone(&self) -> Self {
                 match *self {
                     #(#variant_patterns => #variant_values,)*
@@ -161,11 +163,12 @@ fn derive_clone_enum(name: &Ident, typ: Tokens, generics: &Generics, variants: &
 }
 
 fn derive_clone_struct(name: &Ident, typ: Tokens, generics: &Generics, fields: &[Field]) -> Tokens {
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
     let idents: Vec<_> = fields.iter().map(|field| field.ident.clone().unwrap()).collect();
     let idents1 = &idents;
     let idents2 = &idents;
     quote! {
-        impl #generics Clone for #typ {
+        impl #impl_generics Clone for #typ #where_clause {
             fn clone(&self) -> Self {
                 #name {
                     #(#idents1: self.#idents2.clone(),)*
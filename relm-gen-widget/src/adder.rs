@@ -23,58 +23,231 @@ use std::boxed;
 
 use quote::Tokens;
 use syn;
-use syn::{Expr, ExprKind, Ident, Stmt, parse_expr};
-use syn::ExprKind::{Assign, AssignOp, Block, Field};
-use syn::fold::{Folder, noop_fold_expr};
+use syn::{Delimited, Expr, ExprKind, Ident, Item, ItemKind, Mac, Stmt, TokenTree, parse_expr};
+use syn::ExprKind::{AddrOf, Assign, AssignOp, Block, Call, MethodCall, Unary};
+use syn::fold::{Folder, noop_fold_expr, noop_fold_stmt};
+use syn::Mutability::Mutable;
 use syn::Stmt::Semi;
+use syn::UnOp::Deref;
 use syn::Unsafety::Normal;
 
-use super::PropertyModelMap;
+use diagnostics::report_error;
+use super::{PropertyModelMap, gen_setter_args, is_tuple};
+use walker::{ModelPath, get_model_path, has_return, paths_overlap};
 
 macro_rules! fold_assign {
     ($_self:expr, $lhs:expr, $new_assign:expr) => {{
-        let mut statements = vec![];
-        let new_statements =
-            if let Field(ref field_expr, ref ident) = $lhs.node {
-                if is_model_path(field_expr) {
-                    Some(create_stmts(ident, $_self.map))
-                }
-                else {
-                    None
-                }
-            }
-            else {
-                None
-            };
         let mut new_assign = $new_assign;
-        if let Some(mut stmts) = new_statements {
-            let new_expr = Expr { node: new_assign, attrs: vec![] };
-            statements.push(Semi(boxed::Box::new(new_expr)));
-            statements.append(&mut stmts);
-            new_assign = Block(Normal, syn::Block { stmts: statements })
+        if let Some(path) = get_model_path(&$lhs) {
+            let mut statements = $_self.mark_dirty(&[path]);
+            if !statements.is_empty() {
+                statements.push(Stmt::Expr(boxed::Box::new(Expr { node: new_assign, attrs: vec![] })));
+                new_assign = Block(Normal, syn::Block { stmts: statements });
+            }
         }
         new_assign
     }};
 }
 
+/*
+ * Methods which do not mutate their receiver.
+ * The types of the model fields are not known by the attribute, so any other method called on a
+ * model field is considered to mutate it: at worst, a property is set with its current value.
+ */
+const READ_ONLY_METHODS: &[&str] = &["abs", "as_bytes", "as_ref", "as_slice", "as_str", "borrow", "bytes",
+    "chars", "clone", "cloned", "cmp", "contains", "contains_key", "copied", "ends_with", "eq", "filter", "find",
+    "first", "get", "is_empty", "is_err", "is_none", "is_ok", "is_some", "iter", "keys", "last", "len", "lines",
+    "map", "max", "min", "ne", "partial_cmp", "powi", "split", "sqrt", "starts_with", "to_lowercase",
+    "to_owned", "to_string", "to_uppercase", "to_vec", "trim", "unwrap", "unwrap_or", "unwrap_or_default",
+    "values"];
+
 pub struct Adder<'a> {
+    // The model fields updated by the widgets bound with `bind`.
+    bound_fields: &'a [Tokens],
+    // The properties which need to be set at the end of update().
+    dirty_properties: Vec<&'a Property>,
+    last_values: &'a [Ident],
     map: &'a PropertyModelMap,
 }
 
 impl<'a> Adder<'a> {
-    pub fn new(map: &'a PropertyModelMap) -> Self {
+    pub fn new(map: &'a PropertyModelMap, bound_fields: &'a [Tokens], last_values: &'a [Ident]) -> Self {
         Adder {
+            bound_fields: bound_fields,
+            dirty_properties: vec![],
+            last_values: last_values,
             map: map,
         }
     }
+
+    /*
+     * Create the statements marking the properties using the model fields updated by the bound
+     * widgets as dirty, so that these properties are refreshed by the message of the binding.
+     */
+    fn bound_stmts(&mut self) -> Vec<Stmt> {
+        let mut stmts = vec![];
+        for field in self.bound_fields {
+            let path = parse_expr(field.as_str()).ok().and_then(|expr| get_model_path(&expr));
+            if let Some(path) = path {
+                let mark_stmts = self.mark_dirty(&[path]);
+                if !mark_stmts.is_empty() {
+                    let field = field.as_str();
+                    stmts.push(parse_stmt(quote! {
+                        {
+                            if __relm_bound_fields.contains(&#field) {
+                                #(#mark_stmts)*
+                            }
+                        }
+                    }));
+                }
+            }
+        }
+        if !stmts.is_empty() {
+            stmts.insert(0, parse_stmt(quote! {
+                { let __relm_bound_fields = self.__relm_remote.take_bound_fields(); }
+            }));
+        }
+        stmts
+    }
+
+    /*
+     * Replace the update() body so that the properties changed by this body are set once after it.
+     */
+    pub fn fold_update(&mut self, block: syn::Block) -> syn::Block {
+        let returns = has_return(&block);
+        let block = self.fold_block(block);
+        let bound_stmts = self.bound_stmts();
+        if self.dirty_properties.is_empty() {
+            return block;
+        }
+        let mut stmts = vec![];
+        for index in 0..self.dirty_properties.len() {
+            let dirty = dirty_ident(index);
+            stmts.push(parse_stmt(quote! {
+                { let mut #dirty = false; }
+            }));
+        }
+        stmts.extend(bound_stmts);
+        if returns {
+            // The body is put in a closure so that the properties are still set after a return.
+            stmts.push(parse_stmt(quote! {
+                { (|| #block)(); }
+            }));
+        }
+        else {
+            stmts.push(Semi(boxed::Box::new(Expr { node: Block(Normal, block), attrs: vec![] })));
+        }
+        // The widgets of the conditions and the lists are updated first, so that the properties are
+        // not set on the widgets that are about to be destroyed.
+        let (methods, properties): (Vec<_>, Vec<_>) = self.dirty_properties.iter().enumerate()
+            .partition(|&(_, property)| property.is_method);
+        for (index, property) in methods.into_iter().chain(properties) {
+            let dirty = dirty_ident(index);
+            let call = set_property(property, self.last_values);
+            stmts.push(parse_stmt(quote! {
+                {
+                    if #dirty {
+                        #call
+                    }
+                }
+            }));
+        }
+        syn::Block {
+            stmts: stmts,
+        }
+    }
+
+    /*
+     * Create the statements marking the properties using one of the changed paths as dirty.
+     * A property is dirty when it uses a changed path, or a path under or above it: changing
+     * model.user changes model.user.name and the other way around.
+     */
+    fn mark_dirty(&mut self, changed_paths: &[ModelPath]) -> Vec<Stmt> {
+        let mut properties = vec![];
+        for (path, path_properties) in self.map {
+            if changed_paths.iter().any(|changed_path| paths_overlap(changed_path, path)) {
+                for property in path_properties {
+                    if !properties.contains(&property) {
+                        properties.push(property);
+                    }
+                }
+            }
+        }
+        let mut stmts = vec![];
+        for property in properties {
+            let index =
+                match self.dirty_properties.iter().position(|&dirty_property| dirty_property == property) {
+                    Some(index) => index,
+                    None => {
+                        self.dirty_properties.push(property);
+                        self.dirty_properties.len() - 1
+                    },
+                };
+            let dirty = dirty_ident(index);
+            stmts.push(parse_stmt(quote! {
+                { #dirty = true; }
+            }));
+        }
+        stmts
+    }
+
+    /*
+     * Replace notify!(model.field1, model.field2) by the statements marking the properties bound
+     * to these fields as dirty.
+     */
+    fn notify_stmts(&mut self, mac: &Mac) -> Vec<Stmt> {
+        let tts =
+            match mac.tts.first() {
+                Some(&TokenTree::Delimited(Delimited { ref tts, .. })) => tts,
+                _ => &mac.tts,
+            };
+        let tokens = quote! {
+            [#(#tts)*]
+        };
+        let mut paths = vec![];
+        match parse_expr(tokens.as_str()) {
+            Ok(Expr { node: ExprKind::Array(ref exprs), .. }) => {
+                for expr in exprs {
+                    match get_model_path(expr) {
+                        Some(path) => paths.push(path),
+                        None => report_error(format!("Expected a model field like `model.field` but found `{}` in \
+                            notify!()", quote! { #expr })),
+                    }
+                }
+            },
+            _ => report_error(format!("Expected model fields like `model.field` but found `{}` in notify!()",
+                quote! { #(#tts)* })),
+        }
+        self.mark_dirty(&paths)
+    }
 }
 
 impl<'a> Folder for Adder<'a> {
     fn fold_expr(&mut self, expr: Expr) -> Expr {
+        let expr = fix_deref_assign(expr);
         let lhs_clone =
             match expr.node {
                 Assign(ref lhs, _) | AssignOp(_, ref lhs, _) => lhs.clone(),
-                _ => return noop_fold_expr(self, expr),
+                ExprKind::Mac(ref mac) if is_notify(mac) => {
+                    let stmts = self.notify_stmts(mac);
+                    return Expr {
+                        node: Block(Normal, syn::Block { stmts }),
+                        attrs: expr.attrs.clone(),
+                    };
+                },
+                _ => {
+                    let fields = mutated_fields(&expr);
+                    let mut statements = self.mark_dirty(&fields);
+                    let new_expr = noop_fold_expr(self, expr);
+                    if statements.is_empty() {
+                        return new_expr;
+                    }
+                    statements.push(Stmt::Expr(boxed::Box::new(new_expr)));
+                    return Expr {
+                        node: Block(Normal, syn::Block { stmts: statements }),
+                        attrs: vec![],
+                    };
+                },
             };
         let new_expr = noop_fold_expr(self, expr);
         let new_node = fold_assign!(self, lhs_clone, new_expr.node);
@@ -83,48 +256,362 @@ impl<'a> Folder for Adder<'a> {
             attrs: new_expr.attrs.into_iter().map(|a| self.fold_attribute(a)).collect(),
         }
     }
+
+    fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+        match stmt {
+            // notify!() in statement position is parsed as a macro item.
+            Stmt::Item(ref item) if is_notify_item(item) => {
+                let stmts =
+                    match item.node {
+                        ItemKind::Mac(ref mac) => self.notify_stmts(mac),
+                        _ => unreachable!(),
+                    };
+                Semi(boxed::Box::new(Expr { node: Block(Normal, syn::Block { stmts }), attrs: vec![] }))
+            },
+            Stmt::Mac(ref mac) if is_notify(&mac.0) => {
+                let stmts = self.notify_stmts(&mac.0);
+                Semi(boxed::Box::new(Expr { node: Block(Normal, syn::Block { stmts }), attrs: vec![] }))
+            },
+            stmt => noop_fold_stmt(self, stmt),
+        }
+    }
 }
 
 #[derive(Debug, Eq, Hash, PartialEq)]
 pub struct Property {
     pub expr: String,
+    // The property is the condition of an if or the items of a for: name is the method updating
+    // their widgets.
+    pub is_method: bool,
+    // The widget is in the branch of a condition, so it is stored in an Option.
+    pub is_optional: bool,
     pub is_relm_widget: bool,
     pub name: String,
+    // The property is a child property, set on this parent.
+    pub parent: Option<Parent>,
     pub widget_name: Ident,
 }
 
-fn create_stmts(ident: &Ident, map: &PropertyModelMap) -> Vec<Stmt> {
-    let mut stmts = vec![];
-    if let Some(properties) = map.get(ident) {
-        for property in properties {
-            let widget_name = &property.widget_name;
-            let prop_name = Ident::new(format!("set_{}", property.name));
-            let mut tokens = Tokens::new();
-            tokens.append(&property.expr);
-            let stmt =
-                if property.is_relm_widget {
-                    quote! {
-                        { self.#widget_name.widget().#prop_name(#tokens); }
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Parent {
+    pub is_optional: bool,
+    pub is_relm_widget: bool,
+    pub name: Ident,
+}
+
+fn deref(expr: Expr) -> Expr {
+    Expr { node: Unary(Deref, Box::new(expr)), attrs: vec![] }
+}
+
+fn dirty_ident(index: usize) -> Ident {
+    Ident::new(format!("__relm_dirty{}", index))
+}
+
+/*
+ * syn parses *model = value as *(model = value): move the dereference to the left-hand side.
+ */
+fn fix_deref_assign(expr: Expr) -> Expr {
+    let Expr { node, attrs } = expr;
+    let node =
+        match node {
+            Unary(Deref, inner) => {
+                let inner = *inner;
+                match inner.node {
+                    Assign(lhs, rhs) => Assign(Box::new(deref(*lhs)), rhs),
+                    AssignOp(op, lhs, rhs) => AssignOp(op, Box::new(deref(*lhs)), rhs),
+                    node => Unary(Deref, Box::new(Expr { node, attrs: inner.attrs })),
+                }
+            },
+            node => node,
+        };
+    Expr { node, attrs }
+}
+
+fn is_notify(mac: &Mac) -> bool {
+    mac.path.segments.len() == 1 && mac.path.segments[0].ident == Ident::new("notify")
+}
+
+fn is_notify_item(item: &Item) -> bool {
+    match item.node {
+        ItemKind::Mac(ref mac) => is_notify(mac),
+        _ => false,
+    }
+}
+
+/*
+ * Get the name of the field saving the last value given to the setter of the property.
+ * There is none when the value is not compared, since a setter of a widget in a condition must
+ * always be called, as this widget can be recreated with another value, and the arguments of a
+ * setter with several arguments are not compared.
+ */
+pub fn last_value_ident(property: &Property) -> Option<Ident> {
+    let in_condition = property.is_optional || property.parent.as_ref().map(|parent| parent.is_optional) == Some(true);
+    if property.is_method || in_condition || is_tuple(&property.expr) {
+        return None;
+    }
+    Some(last_value_name(&property.widget_name, &property.name))
+}
+
+pub fn last_value_name(widget_name: &Ident, property_name: &str) -> Ident {
+    Ident::new(format!("__relm_last_{}_{}", widget_name, property_name))
+}
+
+/*
+ * Get the model paths mutated by this expression, without looking at its sub-expressions:
+ * model fields borrowed mutably, as in mem::swap(&mut model.first, &mut model.second), method calls
+ * on a model field, except the read-only methods, and the model itself given to a call.
+ */
+fn mutated_fields(expr: &Expr) -> Vec<ModelPath> {
+    let mut fields = vec![];
+    let args =
+        match expr.node {
+            AddrOf(Mutable, ref expr) => return get_model_path(expr).into_iter().collect(),
+            Call(_, ref args) => &args[..],
+            MethodCall(ref method, _, ref args) => {
+                if !READ_ONLY_METHODS.contains(&method.as_ref()) {
+                    if let Some(path) = get_model_path(&args[0]) {
+                        fields.push(path);
                     }
                 }
-                else {
-                    quote! {
-                        { self.#widget_name.#prop_name(#tokens); }
+                &args[1..]
+            },
+            _ => return fields,
+        };
+    for arg in args {
+        if let ExprKind::Path(..) = arg.node {
+            if let Some(path) = get_model_path(arg) {
+                if !fields.contains(&path) {
+                    fields.push(path);
+                }
+            }
+        }
+    }
+    fields
+}
+
+fn parse_stmt(stmt: Tokens) -> Stmt {
+    let expr = parse_expr(&stmt.parse::<String>().expect("parse::<String>() in create_stmts"))
+        .expect("parse_expr() in create_stmts");
+    if let ExprKind::Block(_, ref block) = expr.node {
+        block.stmts[0].clone()
+    }
+    else {
+        unreachable!()
+    }
+}
+
+/*
+ * Create the statement setting the property, or calling the method updating the widgets of a
+ * condition or a list.
+ */
+fn set_property(property: &Property, last_values: &[Ident]) -> Tokens {
+    let widget_name = &property.widget_name;
+    if property.is_method {
+        let method = Ident::new(property.name.as_ref());
+        return quote! {
+            self.#method(model);
+        };
+    }
+    let prop_name = Ident::new(format!("set_{}", property.name));
+    let last_value = last_value_ident(property).and_then(|ident|
+        if last_values.contains(&ident) {
+            Some(ident)
+        }
+        else {
+            None
+        });
+    let tokens =
+        if last_value.is_some() {
+            quote! { __relm_value }
+        }
+        else {
+            gen_setter_args(&property.expr)
+        };
+    let widget =
+        if property.is_optional {
+            quote! { widget }
+        }
+        else {
+            quote! { self.#widget_name }
+        };
+    let call =
+        if let Some(ref parent) = property.parent {
+            set_child_property(parent, property, &widget, &prop_name, &tokens)
+        }
+        else if property.is_relm_widget {
+            quote! {
+                #widget.widget().#prop_name(#tokens);
+            }
+        }
+        else {
+            quote! {
+                #widget.#prop_name(#tokens);
+            }
+        };
+    if property.is_optional {
+        quote! {
+            if let Some(ref widget) = *self.#widget_name.borrow() {
+                #call
+            }
+        }
+    }
+    else if let Some(last_value) = last_value {
+        // The value is matched instead of being assigned to a variable so that its temporaries live
+        // until the call.
+        let mut expr = Tokens::new();
+        expr.append(&property.expr);
+        quote! {
+            match #expr {
+                __relm_value => {
+                    #[allow(unused_imports)]
+                    use ::relm::{ChangedAlways, ChangedValue};
+                    if (&::relm::Setter(&__relm_value)).changed(&self.#last_value) {
+                        #call
                     }
-                };
-            let expr = parse_expr(&stmt.parse::<String>().expect("parse::<String>() in create_stmts"))
-                .expect("parse_expr() in create_stmts");
-            if let ExprKind::Block(_, ref block) = expr.node {
-                stmts.push(block.stmts[0].clone());
+                },
             }
         }
     }
-    stmts
+    else {
+        call
+    }
+}
+
+fn set_child_property(parent: &Parent, property: &Property, widget: &Tokens, prop_name: &Ident, value: &Tokens)
+    -> Tokens
+{
+    let parent_name = &parent.name;
+    let mut container =
+        if parent.is_optional {
+            quote! { parent }
+        }
+        else {
+            quote! { self.#parent_name }
+        };
+    if parent.is_relm_widget {
+        container = quote! { ::relm::Container::container(#container.widget()) };
+    }
+    let child =
+        if property.is_relm_widget {
+            quote! { #widget.widget().root() }
+        }
+        else {
+            quote! { &#widget }
+        };
+    let call = quote! {
+        #container.#prop_name(#child, #value);
+    };
+    if parent.is_optional {
+        quote! {
+            if let Some(ref parent) = *self.#parent_name.borrow() {
+                #call
+            }
+        }
+    }
+    else {
+        call
+    }
 }
 
-fn is_model_path(expr: &syn::Expr) -> bool {
-    if let ExprKind::Path(_, ref path) = expr.node {
-        return path.segments.len() == 1 && path.segments[0].ident == Ident::new("model");
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use quote::Tokens;
+    use syn::{ExprKind, Ident, parse_expr};
+
+    use super::{Adder, Property, mutated_fields};
+
+    fn label_text(expr: &str) -> Property {
+        Property {
+            expr: expr.to_string(),
+            is_method: false,
+            is_optional: false,
+            is_relm_widget: false,
+            name: "text".to_string(),
+            parent: None,
+            widget_name: Ident::new("label"),
+        }
+    }
+
+    fn fold_update(body: &str, property: Property, last_values: &[Ident]) -> String {
+        let mut map = HashMap::new();
+        let mut properties = HashSet::new();
+        properties.insert(property);
+        map.insert(vec![Ident::new("counter")], properties);
+        let bound_fields: Vec<Tokens> = vec![];
+        let block =
+            match parse_expr(body).expect("parse_expr() in fold_update").node {
+                ExprKind::Block(_, block) => block,
+                _ => panic!("Expected a block"),
+            };
+        let block = Adder::new(&map, &bound_fields, last_values).fold_update(block);
+        quote! { #block }.to_string()
+    }
+
+    fn mutated(expr: &str) -> Vec<String> {
+        let expr = parse_expr(expr).expect("parse_expr() in mutated");
+        mutated_fields(&expr).iter()
+            .map(|path| path.iter().map(|ident| ident.as_ref()).collect::<Vec<_>>().join("."))
+            .collect()
+    }
+
+    #[test]
+    fn method_calls() {
+        assert_eq!(mutated("model.text.push_str(\"a\")"), vec!["text"]);
+        assert_eq!(mutated("model.tasks.iter_mut()"), vec!["tasks"]);
+        assert_eq!(mutated("model.user.names.entry(key)"), vec!["user.names"]);
+        assert!(mutated("model.text.len()").is_empty());
+        assert!(mutated("model.counter.to_string()").is_empty());
+        assert!(mutated("label.set_text(\"a\")").is_empty());
+    }
+
+    #[test]
+    fn mutable_borrows() {
+        assert_eq!(mutated("&mut model.tasks"), vec!["tasks"]);
+        assert_eq!(mutated("&mut model.tasks[0]"), vec!["tasks"]);
+        assert!(mutated("&model.tasks").is_empty());
+        // The arguments are sub-expressions, which are checked on their own.
+        assert!(mutated("mem::swap(&mut model.first, &mut model.second)").is_empty());
+    }
+
+    #[test]
+    fn changed_properties_are_set_at_the_end() {
+        let update = fold_update("{ for _ in 0..10 { model.counter += 1; } }", label_text("model.counter"), &[]);
+        assert!(update.starts_with("{ let mut __relm_dirty0 = false ;"));
+        assert!(update.contains("__relm_dirty0 = true"));
+        assert!(update.ends_with("if __relm_dirty0 { self . label . set_text ( model . counter ) ; } }"));
+        let update = fold_update("{ model.tasks.push(1); }", label_text("model.counter"), &[]);
+        assert!(!update.contains("__relm_dirty0"));
+        let update = fold_update("{ notify!(model.counter); }", label_text("model.counter"), &[]);
+        assert!(update.contains("__relm_dirty0 = true"));
+    }
+
+    #[test]
+    fn properties_set_after_a_return() {
+        let update = fold_update("{ if model.counter > 1 { return; } model.counter += 1; }",
+            label_text("model.counter"), &[]);
+        assert!(update.contains("( | | {"));
+        assert!(update.contains("if __relm_dirty0"));
+    }
+
+    #[test]
+    fn last_values() {
+        let last_values = [Ident::new("__relm_last_label_text")];
+        let update = fold_update("{ model.counter += 1; }", label_text("model.counter"), &last_values);
+        assert!(update.contains("match model . counter { __relm_value =>"));
+        assert!(update.contains(
+            ". changed ( & self . __relm_last_label_text ) { self . label . set_text ( __relm_value ) ;"));
+        // A property with several arguments is not compared.
+        let update = fold_update("{ model.counter += 1; }", label_text("(model.counter, 1)"), &last_values);
+        assert!(!update.contains("__relm_last_label_text"));
+        assert!(update.contains("self . label . set_text ( model . counter , 1 ) ;"));
+    }
+
+    #[test]
+    fn model_given_to_a_call() {
+        assert_eq!(mutated("reset(model)"), vec![""]);
+        assert_eq!(mutated("save(&path, model)"), vec![""]);
+        assert!(mutated("reset(&model.tasks)").is_empty());
     }
-    false
 }
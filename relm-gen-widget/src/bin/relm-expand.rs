@@ -0,0 +1,288 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+/*
+ * Print the code generated for the widgets of a file, to debug the #[widget] attribute.
+ * The widgets are the impls with the #[widget] attribute and the relm_widget! macros of a Rust file,
+ * or the view of a .relm file, which is put in an impl with the minimal methods.
+ * Before each widget, the setters called when a model field is updated are listed: a property not
+ * refreshed after an update is usually missing from this list.
+ * The code is formatted with rustfmt if it is installed.
+ */
+
+#[macro_use]
+extern crate quote;
+extern crate relm_gen_widget;
+extern crate syn;
+
+use std::env;
+use std::fs::File;
+use std::io::{Read, Write, stderr};
+use std::process::{self, Command, Stdio};
+
+use quote::{Tokens, ToTokens};
+use relm_gen_widget::expand_widget;
+use syn::{Delimited, Item, TokenTree, parse_crate, parse_token_trees};
+use syn::DelimToken::{Brace, Bracket, Paren};
+use syn::ItemKind::{Impl, Mac, Mod};
+use syn::Token::{Comma, Semi};
+
+fn main() {
+    let args: Vec<_> = env::args().collect();
+    if args.len() != 2 {
+        exit_with_error(&format!("Usage: {} FILE.rs|FILE.relm", args[0]));
+    }
+    let filename = &args[1];
+    let mut source = String::new();
+    if let Err(error) = File::open(filename).and_then(|mut file| file.read_to_string(&mut source)) {
+        exit_with_error(&format!("Cannot read {}: {}", filename, error));
+    }
+    let widgets =
+        if filename.ends_with(".relm") {
+            vec![relm_file_widget(&source)]
+        }
+        else {
+            match parse_crate(&source) {
+                Ok(krate) => {
+                    let mut widgets = vec![];
+                    collect_widgets(krate.items, &mut widgets);
+                    widgets
+                },
+                Err(error) => exit_with_error(&format!("Cannot parse {}: {}", filename, error)),
+            }
+        };
+    if widgets.is_empty() {
+        exit_with_error(&format!("No widget found in {}", filename));
+    }
+    for widget in widgets {
+        let (tokens, bindings) = expand_widget(widget);
+        println!("// Setters called when the model is updated:");
+        for binding in &bindings {
+            println!("// {}", format_code(binding).trim());
+        }
+        println!("{}", format_code(tokens.as_str()));
+    }
+}
+
+fn exit_with_error(message: &str) -> ! {
+    let _ = writeln!(stderr(), "{}", message);
+    process::exit(1);
+}
+
+/*
+ * Find the impls with the #[widget] attribute and the relm_widget! macros, including in the inline
+ * modules.
+ */
+fn collect_widgets(items: Vec<Item>, widgets: &mut Vec<Tokens>) {
+    for mut item in items {
+        match item.node {
+            Impl(..) if item.attrs.iter().any(|attribute| attribute.name() == "widget") => {
+                item.attrs.retain(|attribute| attribute.name() != "widget");
+                widgets.push(quote! {
+                    #item
+                });
+            },
+            Mac(ref mac) if mac.path.segments.last().map(|segment| segment.ident == "relm_widget") == Some(true) => {
+                for tt in &mac.tts {
+                    if let TokenTree::Delimited(Delimited { ref tts, .. }) = *tt {
+                        let mut tokens = Tokens::new();
+                        tokens.append_all(tts);
+                        widgets.push(tokens);
+                    }
+                }
+            },
+            Mod(Some(items)) => collect_widgets(items, widgets),
+            _ => (),
+        }
+    }
+}
+
+/*
+ * Put the view of a .relm file in an impl, since only the view is in the file.
+ */
+fn relm_file_widget(source: &str) -> Tokens {
+    let mut view = Tokens::new();
+    view.append(source);
+    quote! {
+        impl Widget for View {
+            fn model() -> Model {
+                unimplemented!()
+            }
+
+            fn update(&mut self, _event: Msg, _model: &mut Model) {
+            }
+
+            #view
+        }
+    }
+}
+
+/*
+ * Format the code with rustfmt, or indent it when rustfmt is not available.
+ */
+fn format_code(code: &str) -> String {
+    rustfmt(code).unwrap_or_else(|| indent(code))
+}
+
+fn rustfmt(code: &str) -> Option<String> {
+    let child = Command::new("rustfmt")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child =
+        match child {
+            Ok(child) => child,
+            Err(_) => return None,
+        };
+    if let Some(mut stdin) = child.stdin.take() {
+        if stdin.write_all(code.as_bytes()).is_err() {
+            return None;
+        }
+    }
+    match child.wait_with_output() {
+        Ok(ref output) if output.status.success() => String::from_utf8(output.stdout.clone()).ok(),
+        _ => None,
+    }
+}
+
+/*
+ * Put the items and statements on their own line, indented by the braces.
+ */
+fn indent(code: &str) -> String {
+    match parse_token_trees(code) {
+        Ok(tts) => {
+            let mut printer = Printer::new();
+            printer.print(&tts, true);
+            printer.output
+        },
+        Err(_) => code.to_string(),
+    }
+}
+
+struct Printer {
+    indentation: usize,
+    // No space is needed before the next token.
+    glued: bool,
+    output: String,
+}
+
+impl Printer {
+    fn new() -> Self {
+        Printer {
+            indentation: 0,
+            glued: true,
+            output: String::new(),
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.output.push('\n');
+        for _ in 0..self.indentation {
+            self.output.push_str("    ");
+        }
+        self.glued = true;
+    }
+
+    fn print(&mut self, tts: &[TokenTree], in_braces: bool) {
+        for (index, tt) in tts.iter().enumerate() {
+            let next = tts.get(index + 1);
+            let ends_statement = next != Some(&TokenTree::Token(Semi)) && next != Some(&TokenTree::Token(Comma));
+            match *tt {
+                // The braces of a use are kept on the same line.
+                TokenTree::Delimited(Delimited { delim: Brace, ref tts }) if self.output.ends_with("::") => {
+                    self.write("{");
+                    self.glued = true;
+                    self.print(tts, false);
+                    self.glued = true;
+                    self.write("}");
+                },
+                TokenTree::Delimited(Delimited { delim: Brace, ref tts }) => {
+                    self.write("{");
+                    self.indentation += 1;
+                    self.new_line();
+                    self.print(tts, true);
+                    self.indentation -= 1;
+                    while self.output.ends_with(char::is_whitespace) {
+                        self.output.pop();
+                    }
+                    self.new_line();
+                    self.write("}");
+                    if in_braces && ends_statement {
+                        self.new_line();
+                    }
+                },
+                // An attribute is on its own line.
+                TokenTree::Delimited(Delimited { delim: Bracket, ref tts }) if self.output.ends_with('#') => {
+                    self.write("[");
+                    self.glued = true;
+                    self.print(tts, false);
+                    self.glued = true;
+                    self.write("]");
+                    if in_braces {
+                        self.new_line();
+                    }
+                },
+                TokenTree::Delimited(Delimited { delim, ref tts }) => {
+                    let (open, close) =
+                        match delim {
+                            Bracket => ("[", "]"),
+                            Paren => ("(", ")"),
+                            Brace => unreachable!(),
+                        };
+                    // Calls and indexing are glued to what precedes them.
+                    if self.output.ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '!' || c == '>') {
+                        self.glued = true;
+                    }
+                    self.write(open);
+                    self.glued = true;
+                    self.print(tts, false);
+                    self.glued = true;
+                    self.write(close);
+                },
+                TokenTree::Token(Semi) | TokenTree::Token(Comma) if in_braces => {
+                    self.glued = true;
+                    self.write(if TokenTree::Token(Semi) == *tt { ";" } else { "," });
+                    self.new_line();
+                },
+                TokenTree::Token(_) => {
+                    let mut tokens = Tokens::new();
+                    tt.to_tokens(&mut tokens);
+                    let token = tokens.as_str();
+                    let after_name = self.output.ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '>');
+                    if [",", ";", ".", "?", ":"].contains(&token) || (after_name && (token == "::" || token == "!")) {
+                        self.glued = true;
+                    }
+                    self.write(token);
+                    self.glued = ["&", "#", ".", "::", "!"].contains(&token);
+                },
+            }
+        }
+    }
+
+    fn write(&mut self, string: &str) {
+        if !self.glued {
+            self.output.push(' ');
+        }
+        self.output.push_str(string);
+        self.glued = false;
+    }
+}
@@ -0,0 +1,410 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+/*
+ * Format a view: every entry is on its own line, ending with the comma if it had one, and the
+ * children are indented by four spaces. The last entry of a block written on a single line gets a
+ * trailing comma when the block is expanded. The comments and the single blank lines are kept.
+ * An entry which would be longer than MAX_WIDTH on a single line is kept as written.
+ */
+
+use lexer::{Kind, Token, Tree};
+use lexer::Kind::{Close, Comment, Ident, Literal, Open, Punct};
+use view::{Entry, Item, split_entries};
+
+const MAX_WIDTH: usize = 120;
+
+const KEYWORDS: &[&str] = &["as", "else", "for", "if", "in", "let", "match", "move", "mut", "ref", "return",
+    "while", "with"];
+
+// The previous token written on the line, to know whether a space is needed before the next one.
+struct Previous {
+    glue_after: bool,
+    is_generic_end: bool,
+    kind: Kind,
+    text: String,
+}
+
+struct Formatter<'a> {
+    output: String,
+    source: &'a str,
+}
+
+/*
+ * Format the braces of a view! whose line is indented by indentation levels.
+ */
+pub fn format_view(source: &str, trees: &[Tree], indentation: usize) -> String {
+    let mut formatter = Formatter {
+        output: String::new(),
+        source,
+    };
+    formatter.block(trees, indentation, false);
+    formatter.output
+}
+
+impl<'a> Formatter<'a> {
+    fn block(&mut self, trees: &[Tree], indentation: usize, expanded: bool) {
+        // An empty block is still written on two lines, like `gtk::Frame {\n}`.
+        self.output.push('{');
+        let items = split_entries(trees);
+        let last_entry = items.iter().rposition(|item| match *item {
+            Item::Entry(_) => true,
+            Item::Comment(_) => false,
+        });
+        for (index, item) in items.iter().enumerate() {
+            match *item {
+                Item::Comment(token) if token.newlines_before == 0 => {
+                    self.output.push(' ');
+                    self.output.push_str(&token.text);
+                },
+                Item::Comment(token) => {
+                    self.blank_line(token, index);
+                    self.new_line(indentation + 1);
+                    self.output.push_str(&token.text);
+                },
+                Item::Entry(ref entry) => {
+                    self.blank_line(entry.first(), index);
+                    self.new_line(indentation + 1);
+                    let start = self.output.len();
+                    self.entry(entry, indentation + 1);
+                    let line_start = self.output[..start].rfind('\n').map(|index| index + 1).unwrap_or(0);
+                    let too_long = self.output[line_start..].lines().any(|line| line.chars().count() > MAX_WIDTH);
+                    if too_long && entry.blocks.is_empty() {
+                        self.output.truncate(start);
+                        self.original(entry, indentation + 1);
+                    }
+                    if entry.has_comma || (expanded && Some(index) == last_entry) {
+                        self.output.push(',');
+                    }
+                },
+            }
+        }
+        self.new_line(indentation);
+        self.output.push('}');
+    }
+
+    /*
+     * Keep a blank line before the entry, unless it is the first of the block.
+     */
+    fn blank_line(&mut self, token: &Token, index: usize) {
+        if token.newlines_before > 1 && index > 0 {
+            self.output.push('\n');
+        }
+    }
+
+    fn entry(&mut self, entry: &Entry, indentation: usize) {
+        for attribute in &entry.attributes {
+            self.inline(attribute, &[], indentation, true);
+            self.new_line(indentation);
+        }
+        self.inline(entry.body, &entry.blocks, indentation, false);
+    }
+
+    /*
+     * Write the entry as it is in the source, only changing the indentation of its lines.
+     */
+    fn original(&mut self, entry: &Entry, indentation: usize) {
+        let first = entry.first();
+        let last = entry.body.last().map(Tree::last).unwrap_or(first);
+        let source = self.source;
+        let text = &source[first.offset..last.offset + last.text.len()];
+        let column = first.offset - source[..first.offset].rfind('\n').map(|index| index + 1).unwrap_or(0);
+        for (index, line) in text.lines().enumerate() {
+            if index == 0 {
+                self.output.push_str(line);
+            }
+            else {
+                let content = line.find(|character: char| !character.is_whitespace())
+                    .map(|start| &line[start..])
+                    .unwrap_or("");
+                self.new_line(0);
+                if !content.is_empty() {
+                    // Keep the indentation relative to the first line of the entry.
+                    let spaces = (line.len() - content.len()).saturating_sub(column);
+                    self.output.push_str(&" ".repeat(indentation * 4 + spaces));
+                    self.output.push_str(content);
+                }
+            }
+        }
+    }
+
+    fn inline(&mut self, trees: &[Tree], blocks: &[usize], indentation: usize, is_attribute: bool) {
+        let mut previous: Option<Previous> = None;
+        let mut generic_depth = 0;
+        let mut in_closure_params = false;
+        let mut after_block = false;
+        for (index, tree) in trees.iter().enumerate() {
+            match *tree {
+                Tree::Group { ref close, ref open, ref trees } => {
+                    if blocks.contains(&index) {
+                        if previous.is_some() {
+                            self.output.push(' ');
+                        }
+                        self.block(trees, indentation, open.line == close.line);
+                        after_block = true;
+                    }
+                    else {
+                        if space_before_group(previous.as_ref(), &open.text) {
+                            self.output.push(' ');
+                        }
+                        self.output.push_str(&open.text);
+                        let is_brace = open.text == "{" && !trees.is_empty();
+                        if is_brace {
+                            self.output.push(' ');
+                        }
+                        let attribute_content = is_attribute || previous.as_ref().map(|previous| previous.text == "#") ==
+                            Some(true);
+                        self.inline(trees, &[], indentation, attribute_content);
+                        if is_brace {
+                            self.output.push(' ');
+                        }
+                        self.output.push_str(closing(&open.text));
+                    }
+                    previous = Some(Previous {
+                        glue_after: false,
+                        is_generic_end: false,
+                        kind: Close,
+                        text: "}".to_string(),
+                    });
+                },
+                Tree::Leaf(ref token) if token.kind == Comment => {
+                    if previous.is_some() {
+                        self.output.push(' ');
+                    }
+                    self.output.push_str(&token.text);
+                    if token.text.starts_with("//") {
+                        // The rest of the entry continues on the next line.
+                        self.new_line(indentation + 1);
+                        previous = None;
+                    }
+                },
+                Tree::Leaf(ref token) if token.is("else") && after_block => {
+                    self.new_line(indentation);
+                    self.output.push_str("else");
+                    after_block = false;
+                    previous = Some(Previous {
+                        glue_after: false,
+                        is_generic_end: false,
+                        kind: Ident,
+                        text: "else".to_string(),
+                    });
+                },
+                Tree::Leaf(ref token) => {
+                    after_block = false;
+                    let text = token.text.as_str();
+                    let after_operator = match previous {
+                        None => true,
+                        Some(ref previous) => previous.kind == Punct || previous.kind == Open ||
+                            (previous.kind == Ident && KEYWORDS.contains(&previous.text.as_str())),
+                    };
+                    let is_generic_start = text == "<" && match previous {
+                        Some(ref previous) => previous.text == "::" ||
+                            (previous.kind == Ident && previous.text.starts_with(char::is_uppercase)),
+                        None => false,
+                    };
+                    let is_generic_end = text == ">" && generic_depth > 0;
+                    let is_closure_start = text == "|" && !in_closure_params &&
+                        (after_operator || previous.as_ref().map(|previous| previous.text == "move") == Some(true));
+                    let is_closure_end = text == "|" && in_closure_params;
+                    let glued_before =
+                        match previous {
+                            None => true,
+                            Some(ref previous) =>
+                                previous.glue_after ||
+                                (token.kind == Punct && [",", ";", ".", "?", ":", "@"].contains(&text)) ||
+                                (text == "::" && (previous.kind == Ident || previous.is_generic_end)) ||
+                                (text == "!" && previous.kind == Ident && !KEYWORDS.contains(&previous.text.as_str())) ||
+                                (text == "=" && is_attribute) ||
+                                is_generic_start || is_generic_end || is_closure_end,
+                        };
+                    if !glued_before {
+                        self.output.push(' ');
+                    }
+                    self.output.push_str(text);
+                    if is_generic_start {
+                        generic_depth += 1;
+                    }
+                    if is_generic_end {
+                        generic_depth -= 1;
+                    }
+                    if is_closure_start {
+                        in_closure_params = true;
+                    }
+                    if is_closure_end {
+                        in_closure_params = false;
+                    }
+                    let is_unary = ["&", "&&", "*", "-"].contains(&text) && after_operator;
+                    let glue_after = token.kind == Punct &&
+                        ([".", "::", "#", "@", "!"].contains(&text) || is_unary || is_generic_start || is_closure_start ||
+                         (text == "=" && is_attribute));
+                    previous = Some(Previous {
+                        glue_after,
+                        is_generic_end,
+                        kind: token.kind,
+                        text: text.to_string(),
+                    });
+                },
+            }
+        }
+    }
+
+    fn new_line(&mut self, indentation: usize) {
+        self.output.push('\n');
+        for _ in 0..indentation {
+            self.output.push_str("    ");
+        }
+    }
+}
+
+fn closing(open: &str) -> &'static str {
+    match open {
+        "(" => ")",
+        "[" => "]",
+        _ => "}",
+    }
+}
+
+/*
+ * Calls, indexing and the parameters of an event are glued to what precedes them.
+ */
+fn space_before_group(previous: Option<&Previous>, open: &str) -> bool {
+    match previous {
+        None => false,
+        Some(previous) => {
+            if previous.glue_after {
+                false
+            }
+            else if open == "{" {
+                true
+            }
+            else {
+                match previous.kind {
+                    Ident => KEYWORDS.contains(&previous.text.as_str()),
+                    Literal => !previous.text.ends_with('"'),
+                    Close => false,
+                    _ => !previous.is_generic_end,
+                }
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lexer::{Tree, lex, parse_trees};
+    use super::format_view;
+
+    fn format(source: &str) -> String {
+        let trees = parse_trees(lex(source)).unwrap();
+        match trees[0] {
+            Tree::Group { ref trees, .. } => format_view(source, trees, 0),
+            Tree::Leaf(_) => panic!("Expected a view"),
+        }
+    }
+
+    fn assert_idempotent(source: &str) {
+        let formatted = format(source);
+        assert_eq!(format(&formatted), formatted);
+    }
+
+    #[test]
+    fn formatted_view_is_kept() {
+        let source = "{
+    gtk::Window {
+        // The content.
+        gtk::Box {
+            orientation: Vertical,
+            #[name=\"label\"]
+            gtk::Label {
+                text: &model.counter.to_string(),
+            },
+
+            gtk::Button {
+                clicked => Increment,
+                label: \"+\",
+            },
+        },
+        delete_event(_, _) => (Quit, Inhibit(false)),
+    }
+}";
+        assert_eq!(format(source), source);
+    }
+
+    #[test]
+    fn single_line_block_is_expanded() {
+        let source = "{
+    gtk::Box { gtk::Label { text: \"a\" }, packing: { expand: true } },
+}";
+        assert_eq!(format(source), "{
+    gtk::Box {
+        gtk::Label {
+            text: \"a\",
+        },
+        packing: {
+            expand: true,
+        },
+    },
+}");
+        assert_idempotent(source);
+    }
+
+    #[test]
+    fn spaces() {
+        let source = "{
+    gtk::Label{text:&format!(\"{}\",model . count),clicked(_)=>move|x|Click(x),
+        visible:!model.hidden&&model.count>0,}
+}";
+        assert_eq!(format(source), "{
+    gtk::Label {
+        text: &format!(\"{}\", model.count),
+        clicked(_) => move |x| Click(x),
+        visible: !model.hidden && model.count > 0,
+    }
+}");
+        assert_idempotent(source);
+    }
+
+    #[test]
+    fn control_flow() {
+        let source = "{
+    gtk::Box {
+        if model.visible { gtk::Label { text: \"yes\" } } else { gtk::Label { text: \"no\" } },
+        for item in model.items.iter() { gtk::Label { text: item } },
+    }
+}";
+        let formatted = format(source);
+        assert!(formatted.contains("        }\n        else {\n"));
+        assert!(formatted.contains("for item in model.items.iter() {\n"));
+        assert_idempotent(source);
+    }
+
+    #[test]
+    fn long_entry_is_kept() {
+        let value = "model.a".repeat(20);
+        let source = format!("{{\n    gtk::Label {{\n        text: &({}\n            + 1),\n    }}\n}}", value);
+        assert_eq!(format(&source), source);
+    }
+
+    #[test]
+    fn empty_block() {
+        assert_eq!(format("{ gtk::Frame {} }"), "{\n    gtk::Frame {\n    }\n}");
+    }
+}
@@ -0,0 +1,427 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+/*
+ * A lexer keeping the comments and the position of the tokens, which syn does not, so that the view
+ * can be formatted without losing the comments and the warnings can show a line.
+ */
+
+use std::str::Chars;
+use std::iter::Peekable;
+
+use self::Kind::*;
+
+// The operators made of many characters: > is always alone, since it can close nested generics.
+const OPERATORS: &[&str] = &["::", "=>", "->", "==", "!=", "<=", ">=", "&&", "||", "..", "+=", "-=", "*=", "/="];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Close,
+    Comment,
+    Ident,
+    Literal,
+    Open,
+    Punct,
+}
+
+#[derive(Clone, Debug)]
+pub struct Token {
+    pub kind: Kind,
+    pub line: usize,
+    pub newlines_before: usize,
+    pub offset: usize,
+    pub text: String,
+}
+
+impl Token {
+    pub fn is(&self, text: &str) -> bool {
+        self.kind != Comment && self.kind != Literal && self.text == text
+    }
+}
+
+pub enum Tree {
+    Group {
+        close: Token,
+        open: Token,
+        trees: Vec<Tree>,
+    },
+    Leaf(Token),
+}
+
+impl Tree {
+    /*
+     * The first token of the tree, which gives its position.
+     */
+    pub fn first(&self) -> &Token {
+        match *self {
+            Tree::Group { ref open, .. } => open,
+            Tree::Leaf(ref token) => token,
+        }
+    }
+
+    /*
+     * The last token of the tree, which is the closing delimiter of a group.
+     */
+    pub fn last(&self) -> &Token {
+        match *self {
+            Tree::Group { ref close, .. } => close,
+            Tree::Leaf(ref token) => token,
+        }
+    }
+
+    pub fn is(&self, text: &str) -> bool {
+        match *self {
+            Tree::Leaf(ref token) => token.is(text),
+            Tree::Group { .. } => false,
+        }
+    }
+
+    pub fn is_comment(&self) -> bool {
+        self.first().kind == Comment
+    }
+
+    /*
+     * The delimiter of the group, like '{'.
+     */
+    pub fn delimiter(&self) -> Option<&str> {
+        match *self {
+            Tree::Group { ref open, .. } => Some(&open.text),
+            Tree::Leaf(_) => None,
+        }
+    }
+}
+
+struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    newlines: usize,
+    offset: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn next(&mut self) -> Option<char> {
+        let character = self.chars.next();
+        if let Some(character) = character {
+            self.offset += character.len_utf8();
+            if character == '\n' {
+                self.line += 1;
+            }
+        }
+        character
+    }
+
+    fn next_if<F: Fn(char) -> bool>(&mut self, predicate: F) -> Option<char> {
+        match self.chars.peek() {
+            Some(&character) if predicate(character) => self.next(),
+            _ => None,
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().cloned()
+    }
+
+    fn skip_while<F: Fn(char) -> bool>(&mut self, text: &mut String, predicate: F) {
+        while let Some(character) = self.next_if(&predicate) {
+            text.push(character);
+        }
+    }
+
+    fn block_comment(&mut self, text: &mut String) {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                Some(character) => {
+                    text.push(character);
+                    if text.ends_with("/*") {
+                        depth += 1;
+                    }
+                    else if text.ends_with("*/") {
+                        depth -= 1;
+                    }
+                },
+                None => break,
+            }
+        }
+    }
+
+    fn quoted(&mut self, text: &mut String, quote: char) {
+        while let Some(character) = self.next() {
+            text.push(character);
+            if character == '\\' {
+                if let Some(character) = self.next() {
+                    text.push(character);
+                }
+            }
+            else if character == quote {
+                break;
+            }
+        }
+    }
+
+    fn raw_string(&mut self, text: &mut String) {
+        let mut hashes = 0;
+        while self.next_if(|character| character == '#').is_some() {
+            text.push('#');
+            hashes += 1;
+        }
+        if self.next_if(|character| character == '"').is_some() {
+            text.push('"');
+        }
+        let end: String = Some('"').into_iter().chain((0..hashes).map(|_| '#')).collect();
+        while let Some(character) = self.next() {
+            text.push(character);
+            if text.ends_with(&end) {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self) -> Option<Token> {
+        self.newlines = 0;
+        while let Some(character) = self.next_if(char::is_whitespace) {
+            if character == '\n' {
+                self.newlines += 1;
+            }
+        }
+        let offset = self.offset;
+        let line = self.line;
+        let first = match self.next() {
+            Some(character) => character,
+            None => return None,
+        };
+        let mut text = first.to_string();
+        let kind =
+            match first {
+                '/' if self.peek() == Some('/') => {
+                    self.skip_while(&mut text, |character| character != '\n');
+                    Comment
+                },
+                '/' if self.peek() == Some('*') => {
+                    text.push(self.next().expect("*"));
+                    self.block_comment(&mut text);
+                    Comment
+                },
+                'r' if self.peek() == Some('"') || self.peek() == Some('#') => {
+                    self.raw_string(&mut text);
+                    Literal
+                },
+                'b' if self.peek() == Some('"') || self.peek() == Some('\'') => {
+                    let quote = self.next().expect("quote");
+                    text.push(quote);
+                    self.quoted(&mut text, quote);
+                    Literal
+                },
+                '"' => {
+                    self.quoted(&mut text, '"');
+                    Literal
+                },
+                '\'' => {
+                    // A char literal, or a lifetime.
+                    let mut lookahead = self.chars.clone();
+                    let is_char = lookahead.next() == Some('\\') || lookahead.next() == Some('\'');
+                    if is_char {
+                        self.quoted(&mut text, '\'');
+                        Literal
+                    }
+                    else {
+                        self.skip_while(&mut text, |character| character.is_alphanumeric() || character == '_');
+                        Ident
+                    }
+                },
+                '(' | '[' | '{' => Open,
+                ')' | ']' | '}' => Close,
+                character if character.is_alphabetic() || character == '_' => {
+                    self.skip_while(&mut text, |character| character.is_alphanumeric() || character == '_');
+                    Ident
+                },
+                character if character.is_digit(10) => {
+                    loop {
+                        self.skip_while(&mut text, |character| character.is_alphanumeric() || character == '_');
+                        // The dot of a float, but not of a range or a method call.
+                        let mut lookahead = self.chars.clone();
+                        if lookahead.next() == Some('.') && lookahead.next().map(|character| character.is_digit(10)) ==
+                            Some(true)
+                        {
+                            text.push(self.next().expect("."));
+                        }
+                        else {
+                            break;
+                        }
+                    }
+                    Literal
+                },
+                _ => {
+                    if let Some(next) = self.peek() {
+                        let operator = format!("{}{}", first, next);
+                        if OPERATORS.contains(&operator.as_str()) {
+                            self.next();
+                            text = operator;
+                        }
+                    }
+                    Punct
+                },
+            };
+        Some(Token {
+            kind,
+            line,
+            newlines_before: self.newlines,
+            offset,
+            text,
+        })
+    }
+}
+
+pub fn lex(source: &str) -> Vec<Token> {
+    let mut lexer = Lexer {
+        chars: source.chars().peekable(),
+        line: 1,
+        newlines: 0,
+        offset: 0,
+    };
+    let mut tokens = vec![];
+    while let Some(token) = lexer.token() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+/*
+ * Group the tokens by their delimiters.
+ */
+pub fn parse_trees(tokens: Vec<Token>) -> Result<Vec<Tree>, String> {
+    let mut stack: Vec<(Token, Vec<Tree>)> = vec![];
+    let mut trees = vec![];
+    for token in tokens {
+        match token.kind {
+            Open => {
+                let parent_trees = ::std::mem::replace(&mut trees, vec![]);
+                stack.push((token, parent_trees));
+            },
+            Close => {
+                let (open, parent_trees) =
+                    match stack.pop() {
+                        Some(group) => group,
+                        None => return Err(format!("line {}: unexpected `{}`", token.line, token.text)),
+                    };
+                if closing_delimiter(&open.text) != token.text {
+                    return Err(format!("line {}: `{}` closed by `{}` on line {}", open.line, open.text, token.text,
+                        token.line));
+                }
+                let group_trees = ::std::mem::replace(&mut trees, parent_trees);
+                trees.push(Tree::Group {
+                    close: token,
+                    open,
+                    trees: group_trees,
+                });
+            },
+            _ => trees.push(Tree::Leaf(token)),
+        }
+    }
+    match stack.pop() {
+        Some((open, _)) => Err(format!("line {}: unclosed `{}`", open.line, open.text)),
+        None => Ok(trees),
+    }
+}
+
+fn closing_delimiter(open: &str) -> &str {
+    match open {
+        "(" => ")",
+        "[" => "]",
+        _ => "}",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lex, parse_trees};
+    use super::Kind::{Close, Comment, Ident, Literal, Open, Punct};
+
+    const SOURCE: &str = r##"gtk::Box {
+    // A comment.
+    #[name="label"]
+    gtk::Label {
+        text: &format!("{}/*", 'a'), /* nested /* comment */ */
+        xalign: 0.5,
+        visible: model.range.start..model.range.end != 1.max(2),
+        label: r#"raw "string""#,
+        name: b'x' == b"x"[0],
+    },
+    clicked(_) => move |x: &'static str| x,
+}"##;
+
+    #[test]
+    fn tokens_cover_the_source() {
+        let tokens = lex(SOURCE);
+        let mut end = 0;
+        for token in &tokens {
+            assert_eq!(&SOURCE[token.offset..token.offset + token.text.len()], token.text);
+            assert!(SOURCE[end..token.offset].trim().is_empty());
+            assert_eq!(token.line, SOURCE[..token.offset].matches('\n').count() + 1);
+            end = token.offset + token.text.len();
+        }
+        assert_eq!(end, SOURCE.len());
+    }
+
+    #[test]
+    fn kinds() {
+        let tokens = lex(SOURCE);
+        let kind = |text: &str| tokens.iter().find(|token| token.text == text).map(|token| token.kind);
+        assert_eq!(kind("// A comment."), Some(Comment));
+        assert_eq!(kind("/* nested /* comment */ */"), Some(Comment));
+        assert_eq!(kind("\"{}/*\""), Some(Literal));
+        assert_eq!(kind("'a'"), Some(Literal));
+        assert_eq!(kind("'static"), Some(Ident));
+        assert_eq!(kind("0.5"), Some(Literal));
+        assert_eq!(kind(".."), Some(Punct));
+        assert_eq!(kind("r#\"raw \"string\"\"#"), Some(Literal));
+        assert_eq!(kind("b'x'"), Some(Literal));
+        assert_eq!(kind("b\"x\""), Some(Literal));
+        assert_eq!(kind("=>"), Some(Punct));
+        assert_eq!(kind("{"), Some(Open));
+        assert_eq!(kind("}"), Some(Close));
+        // A method call on an integer is not a float.
+        assert_eq!(kind("1"), Some(Literal));
+        assert_eq!(kind("max"), Some(Ident));
+    }
+
+    #[test]
+    fn newlines_before() {
+        let tokens = lex("a\n\n  b c");
+        assert_eq!(tokens[1].newlines_before, 2);
+        assert_eq!(tokens[2].newlines_before, 0);
+    }
+
+    #[test]
+    fn trees() {
+        let trees = parse_trees(lex(SOURCE)).unwrap();
+        assert_eq!(trees.len(), 4);
+        assert_eq!(trees[3].delimiter(), Some("{"));
+        assert_eq!(trees[3].last().text, "}");
+    }
+
+    #[test]
+    fn unbalanced_delimiters() {
+        assert_eq!(parse_trees(lex("{\n(]")).err(), Some("line 2: `(` closed by `]` on line 2".to_string()));
+        assert_eq!(parse_trees(lex("a }")).err(), Some("line 1: unexpected `}`".to_string()));
+        assert_eq!(parse_trees(lex("\n[")).err(), Some("line 2: unclosed `[`".to_string()));
+    }
+}
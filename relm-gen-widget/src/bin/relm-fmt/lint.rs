@@ -0,0 +1,322 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+/*
+ * Find the mistakes in a view which are not errors of the #[widget] attribute.
+ */
+
+use std::collections::HashMap;
+
+use relm_gen_widget::{reads_model, reads_variable};
+
+use lexer::{Token, Tree};
+use lexer::Kind::{Comment, Ident, Literal};
+use view::{Entry, Item, split_entries};
+
+pub struct Warning {
+    pub line: usize,
+    pub message: String,
+}
+
+struct Linter<'a> {
+    // The widgets the events are sent to, as in `clicked => label@Reset`.
+    foreign_widgets: Vec<&'a Token>,
+    names: Vec<(String, usize)>,
+    warnings: Vec<Warning>,
+}
+
+/*
+ * Lint the braces of a view!. The tokens of the whole file are used to find the named widgets which
+ * are used outside of the view.
+ */
+pub fn lint_view(trees: &[Tree], file_tokens: &[Token]) -> Vec<Warning> {
+    let mut linter = Linter {
+        foreign_widgets: vec![],
+        names: vec![],
+        warnings: vec![],
+    };
+    linter.block(trees);
+    for &(ref name, line) in &linter.names {
+        if !is_name_used(name, file_tokens) {
+            linter.warnings.push(Warning {
+                line,
+                message: format!("the widget `{}` is named, but the name is never used", name),
+            });
+        }
+    }
+    for widget in &linter.foreign_widgets {
+        if !linter.names.iter().any(|&(ref name, _)| *name == widget.text) {
+            linter.warnings.push(Warning {
+                line: widget.line,
+                message: format!("the message is sent to `{}`, which is not the name of a widget of the view",
+                    widget.text),
+            });
+        }
+    }
+    linter.warnings.sort_by_key(|warning| warning.line);
+    linter.warnings
+}
+
+impl<'a> Linter<'a> {
+    /*
+     * Check the properties, the events and the children in a block.
+     */
+    fn block(&mut self, trees: &'a [Tree]) {
+        let mut properties = HashMap::new();
+        let mut child_properties = HashMap::new();
+        for item in split_entries(trees) {
+            if let Item::Entry(entry) = item {
+                self.attributes(&entry);
+                // The child properties are checked with the properties, since they are not widgets.
+                let is_property = entry.body.get(1).map(|tree| tree.is(":")) == Some(true);
+                if !is_property {
+                    for &index in &entry.blocks {
+                        if let Tree::Group { ref trees, .. } = entry.body[index] {
+                            self.block(trees);
+                        }
+                    }
+                }
+                if entry.blocks.is_empty() || is_property {
+                    self.entry(&entry, &mut properties, &mut child_properties);
+                }
+            }
+        }
+    }
+
+    fn attributes(&mut self, entry: &Entry) {
+        for attribute in &entry.attributes {
+            if let Tree::Group { ref trees, .. } = attribute[1] {
+                if let (Some(&Tree::Leaf(ref name)), Some(&Tree::Leaf(ref value))) = (trees.get(0), trees.get(2)) {
+                    if name.is("name") && trees[1].is("=") && value.kind == Literal {
+                        self.names.push((value.text.trim_matches('"').to_string(), value.line));
+                    }
+                }
+            }
+        }
+    }
+
+    fn entry(&mut self, entry: &Entry<'a>, properties: &mut HashMap<String, usize>,
+        child_properties: &mut HashMap<String, usize>)
+    {
+        let body = entry.body;
+        let is_property = body.get(1).map(|tree| tree.is(":")) == Some(true);
+        if !is_property {
+            if let Some(position) = body.iter().position(|tree| tree.is("=>")) {
+                // An event: check the widget receiving the message.
+                if let (Some(&Tree::Leaf(ref widget)), Some(at)) = (body.get(position + 1), body.get(position + 2)) {
+                    if widget.kind == Ident && at.is("@") {
+                        self.foreign_widgets.push(widget);
+                    }
+                }
+            }
+        }
+        else if let Some(name) = entry.name() {
+            match body.get(2) {
+                Some(&Tree::Group { ref open, ref trees, .. }) if open.text == "{" && body.len() == 3 => {
+                    for item in split_entries(trees) {
+                        if let Item::Entry(child_entry) = item {
+                            if let Some(child_name) = child_entry.name() {
+                                self.check_duplicate(child_properties, child_name, "child property");
+                            }
+                        }
+                    }
+                    return;
+                },
+                Some(tree) if tree.is("bind") => {
+                    // The model field is followed by the message sent when the property changes.
+                    let end = body.iter().position(|tree| tree.is("=>")).unwrap_or(body.len());
+                    if reads_model(&trees_text(&body[3..end])) == Some(false) {
+                        self.warnings.push(Warning {
+                            line: name.line,
+                            message: format!("`bind` has no effect on the property `{}`, since its value does not \
+                                use the model", name.text),
+                        });
+                    }
+                },
+                // A property is only updated with the model fields it reads, so a value reading other
+                // variables is only set when the view is created.
+                Some(_) => {
+                    let value = trees_text(&body[2..]);
+                    if reads_model(&value) == Some(false) && reads_variable(&value) == Some(true) {
+                        self.warnings.push(Warning {
+                            line: name.line,
+                            message: format!("the property `{}` never changes, since its value reads no model field",
+                                name.text),
+                        });
+                    }
+                },
+                None => (),
+            }
+            self.check_duplicate(properties, name, "property");
+        }
+    }
+
+    fn check_duplicate(&mut self, names: &mut HashMap<String, usize>, name: &Token, kind: &str) {
+        if let Some(&line) = names.get(&name.text) {
+            self.warnings.push(Warning {
+                line: name.line,
+                message: format!("the {} `{}` is already set on line {}: only the last value is used", kind,
+                    name.text, line),
+            });
+        }
+        else {
+            names.insert(name.text.clone(), name.line);
+        }
+    }
+}
+
+/*
+ * A name is used to send a message to the widget, as in `clicked => label@Reset`, or to access the
+ * widget, as in `self.label` or `widgets.label`.
+ */
+fn is_name_used(name: &str, file_tokens: &[Token]) -> bool {
+    let tokens: Vec<_> = file_tokens.iter()
+        .filter(|token| token.kind != Comment)
+        .collect();
+    tokens.iter().enumerate().any(|(index, token)| {
+        if token.kind != Ident || token.text != name {
+            return false;
+        }
+        let previous = if index > 0 { Some(tokens[index - 1]) } else { None };
+        let is_message_target = tokens.get(index + 1).map(|token| token.is("@")) == Some(true) ||
+            previous.map(|token| token.is("@")) == Some(true);
+        let is_field = index > 1 && tokens[index - 1].is(".") &&
+            (tokens[index - 2].is("self") || tokens[index - 2].is("widgets"));
+        is_message_target || is_field
+    })
+}
+
+/*
+ * Get the source of an expression, without its comments.
+ */
+fn trees_text(trees: &[Tree]) -> String {
+    let mut texts = vec![];
+    add_trees_text(trees, &mut texts);
+    texts.join(" ")
+}
+
+fn add_trees_text<'a>(trees: &'a [Tree], texts: &mut Vec<&'a str>) {
+    for tree in trees {
+        match *tree {
+            Tree::Group { ref open, ref trees, ref close } => {
+                texts.push(&open.text);
+                add_trees_text(trees, texts);
+                texts.push(&close.text);
+            },
+            Tree::Leaf(ref token) => {
+                if token.kind != Comment {
+                    texts.push(&token.text);
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lexer::{Tree, lex, parse_trees};
+    use super::lint_view;
+
+    fn lint(source: &str) -> Vec<(usize, String)> {
+        let tokens = lex(source);
+        let trees = parse_trees(tokens.clone()).unwrap();
+        match trees[0] {
+            Tree::Group { ref trees, .. } => lint_view(trees, &tokens).into_iter()
+                .map(|warning| (warning.line, warning.message))
+                .collect(),
+            Tree::Leaf(_) => panic!("Expected a view"),
+        }
+    }
+
+    #[test]
+    fn bind() {
+        let warnings = lint("{
+    gtk::Entry {
+        text: bind &model.text => TextChanged,
+        visible: bind self.visible,
+    }
+}");
+        assert_eq!(warnings, vec![(4, "`bind` has no effect on the property `visible`, since its value does not use \
+            the model".to_string())]);
+    }
+
+    #[test]
+    fn unchanging_properties() {
+        let warnings = lint("{
+    gtk::Label {
+        halign: gtk::Align::Center,
+        max_width_chars: 10,
+        text: &model.text,
+        tooltip_text: &tooltip, // Set once.
+        visible: self.visible,
+        name: &tr(\"label\"),
+    }
+}");
+        assert_eq!(warnings, vec![
+            (6, "the property `tooltip_text` never changes, since its value reads no model field".to_string()),
+            (7, "the property `visible` never changes, since its value reads no model field".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn used_names() {
+        let view = "{
+    #[name=\"label\"]
+    gtk::Label {
+        text: &model.entry,
+    },
+    #[name=\"entry\"]
+    gtk::Entry {},
+    #[name=\"button\"]
+    gtk::Button {
+        clicked => label@Reset,
+    },
+}";
+        let file = format!("fn init_view(&mut self) {{ self.button.grab_focus(); }} view! {}", view);
+        let tokens = lex(view);
+        let trees = parse_trees(tokens).unwrap();
+        let warnings: Vec<_> =
+            match trees[0] {
+                Tree::Group { ref trees, .. } => lint_view(trees, &lex(&file)).into_iter()
+                    .map(|warning| (warning.line, warning.message))
+                    .collect(),
+                Tree::Leaf(_) => panic!("Expected a view"),
+            };
+        assert_eq!(warnings, vec![(6, "the widget `entry` is named, but the name is never used".to_string())]);
+    }
+
+    #[test]
+    fn names_and_duplicates() {
+        let warnings = lint("{
+    #[name=\"label\"]
+    gtk::Label {
+        text: \"a\",
+        text: \"b\",
+        clicked => button@Click,
+    }
+}");
+        assert_eq!(warnings, vec![
+            (2, "the widget `label` is named, but the name is never used".to_string()),
+            (5, "the property `text` is already set on line 4: only the last value is used".to_string()),
+            (6, "the message is sent to `button`, which is not the name of a widget of the view".to_string()),
+        ]);
+    }
+}
@@ -0,0 +1,152 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+/*
+ * Format and lint the view! of the widgets of a Rust file and the view of a .relm file.
+ * Only the braces of the view! are changed: the rest of the file is left to rustfmt.
+ * The comments are kept.
+ *
+ * Usage: relm-fmt [--check|--lint] FILE...
+ * Without option, the files are formatted in place.
+ * With --check, the files which are not formatted are printed and the files are not modified.
+ * With --lint, the mistakes in the views are printed.
+ * In both cases, the exit status is 1 when something is printed.
+ */
+
+extern crate relm_gen_widget;
+
+mod format;
+mod lexer;
+mod lint;
+mod view;
+
+use std::env;
+use std::fs::File;
+use std::io::{Read, Write, stderr};
+use std::process;
+
+use format::format_view;
+use lexer::{Token, Tree, lex, parse_trees};
+use lint::lint_view;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Check,
+    Format,
+    Lint,
+}
+
+fn main() {
+    let mut args: Vec<_> = env::args().collect();
+    let program = args.remove(0);
+    let mode =
+        match args.first().map(|arg| arg.as_str()) {
+            Some("--check") => Mode::Check,
+            Some("--lint") => Mode::Lint,
+            _ => Mode::Format,
+        };
+    if mode != Mode::Format {
+        args.remove(0);
+    }
+    if args.is_empty() {
+        exit_with_error(&format!("Usage: {} [--check|--lint] FILE.rs|FILE.relm...", program));
+    }
+    let mut failed = false;
+    for filename in &args {
+        let mut source = String::new();
+        if let Err(error) = File::open(filename).and_then(|mut file| file.read_to_string(&mut source)) {
+            exit_with_error(&format!("Cannot read {}: {}", filename, error));
+        }
+        let tokens = lex(&source);
+        let trees =
+            match parse_trees(tokens.clone()) {
+                Ok(trees) => trees,
+                Err(error) => exit_with_error(&format!("Cannot parse {}: {}", filename, error)),
+            };
+        let mut views = vec![];
+        collect_views(&trees, &mut views);
+        match mode {
+            Mode::Check | Mode::Format => {
+                let formatted = format_source(&source, &views);
+                if formatted != source {
+                    if mode == Mode::Check {
+                        println!("{}", filename);
+                        failed = true;
+                    }
+                    else if let Err(error) = File::create(filename)
+                        .and_then(|mut file| file.write_all(formatted.as_bytes()))
+                    {
+                        exit_with_error(&format!("Cannot write {}: {}", filename, error));
+                    }
+                }
+            },
+            Mode::Lint => {
+                for &(_, view) in &views {
+                    for warning in lint_view(view, &tokens) {
+                        println!("{}:{}: warning: {}", filename, warning.line, warning.message);
+                        failed = true;
+                    }
+                }
+            },
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn exit_with_error(message: &str) -> ! {
+    let _ = writeln!(stderr(), "{}", message);
+    process::exit(1);
+}
+
+/*
+ * Find the braces following `view!`, including in the macros like relm_widget!.
+ * The open and close braces are returned with the trees inside them.
+ */
+fn collect_views<'a>(trees: &'a [Tree], views: &mut Vec<((&'a Token, &'a Token, &'a Token), &'a [Tree])>) {
+    for (index, tree) in trees.iter().enumerate() {
+        if let Tree::Group { ref close, ref open, trees: ref inner_trees } = *tree {
+            let is_view = index >= 2 && trees[index - 2].is("view") && trees[index - 1].is("!");
+            if is_view && open.text == "{" {
+                views.push(((trees[index - 2].first(), open, close), inner_trees));
+            }
+            else {
+                collect_views(inner_trees, views);
+            }
+        }
+    }
+}
+
+/*
+ * Replace the braces of the views by their formatted version.
+ */
+fn format_source(source: &str, views: &[((&Token, &Token, &Token), &[Tree])]) -> String {
+    let mut result = source.to_string();
+    // Replace from the end so that the offsets of the previous views stay valid.
+    for &((view, open, close), trees) in views.iter().rev() {
+        let line_start = source[..view.offset].rfind('\n').map(|index| index + 1).unwrap_or(0);
+        let indentation = source[line_start..].chars().take_while(|&c| c == ' ').count() / 4;
+        let end = close.offset + close.text.len();
+        result = format!("{}{}{}", &result[..open.offset], format_view(source, trees, indentation), &result[end..]);
+    }
+    result
+}
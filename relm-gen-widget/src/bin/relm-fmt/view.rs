@@ -0,0 +1,185 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+/*
+ * Split the content of a block of the view in entries: the properties, events and children separated
+ * by commas.
+ */
+
+use lexer::{Token, Tree};
+use lexer::Kind::{Comment, Ident};
+
+pub enum Item<'a> {
+    Comment(&'a Token),
+    Entry(Entry<'a>),
+}
+
+pub struct Entry<'a> {
+    // The attributes, like #[name="label"], each made of the # and the brackets.
+    pub attributes: Vec<&'a [Tree]>,
+    // The indices of the braces in the body containing entries, like the children of a widget, the
+    // branches of an if or the child properties.
+    pub blocks: Vec<usize>,
+    pub body: &'a [Tree],
+    pub has_comma: bool,
+}
+
+impl<'a> Entry<'a> {
+    fn new(trees: &'a [Tree], has_comma: bool) -> Self {
+        let body_start = skip_attributes(trees, 0);
+        let attributes = (0..body_start / 2)
+            .map(|index| &trees[index * 2..index * 2 + 2])
+            .collect();
+        let body = &trees[body_start..];
+        let blocks = (0..body.len())
+            .filter(|&index| is_block(body, index))
+            .collect();
+        Entry {
+            attributes,
+            blocks,
+            body,
+            has_comma,
+        }
+    }
+
+    pub fn first(&self) -> &'a Token {
+        match self.attributes.first() {
+            Some(attribute) => attribute[0].first(),
+            None => self.body[0].first(),
+        }
+    }
+
+    /*
+     * The name of a property or an event, which is the first identifier.
+     */
+    pub fn name(&self) -> Option<&'a Token> {
+        match self.body.first() {
+            Some(&Tree::Leaf(ref token)) if token.kind == Ident => Some(token),
+            _ => None,
+        }
+    }
+}
+
+pub fn split_entries<'a>(trees: &'a [Tree]) -> Vec<Item<'a>> {
+    let mut items = vec![];
+    let mut index = 0;
+    while index < trees.len() {
+        if let Tree::Leaf(ref token) = trees[index] {
+            if token.kind == Comment {
+                items.push(Item::Comment(token));
+                index += 1;
+                continue;
+            }
+        }
+        let start = index;
+        let body_start = skip_attributes(trees, start);
+        let is_control = is_control(&trees[body_start..]);
+        let mut generic_depth = 0;
+        let mut has_block = false;
+        while index < trees.len() && !is_trailing_comment(&trees[index..]) {
+            let tree = &trees[index];
+            // The commas of the generic arguments and of the iterator of a for do not end the entry.
+            if tree.is(",") && generic_depth == 0 && (!is_control || has_block) {
+                break;
+            }
+            if tree.is("<") && index > start && is_generic_start(&trees[index - 1]) {
+                generic_depth += 1;
+            }
+            else if tree.is(">") && generic_depth > 0 {
+                generic_depth -= 1;
+            }
+            index += 1;
+            // A child ends with its braces, even without a comma, unless an else follows.
+            if index > body_start && is_block(&trees[body_start..index], index - 1 - body_start) {
+                has_block = true;
+                if index >= trees.len() || !trees[index].is("else") {
+                    break;
+                }
+            }
+        }
+        let end = index;
+        // The comments between the entry and its comma are put after the comma.
+        while index < trees.len() && trees[index].is_comment() {
+            index += 1;
+        }
+        let comments = &trees[end..index];
+        let has_comma = index < trees.len() && trees[index].is(",");
+        if has_comma {
+            index += 1;
+        }
+        if end > start {
+            items.push(Item::Entry(Entry::new(&trees[start..end], has_comma)));
+        }
+        for comment in comments {
+            if let Tree::Leaf(ref token) = *comment {
+                items.push(Item::Comment(token));
+            }
+        }
+    }
+    items
+}
+
+fn skip_attributes(trees: &[Tree], mut index: usize) -> usize {
+    while index + 1 < trees.len() && trees[index].is("#") && trees[index + 1].delimiter() == Some("[") {
+        index += 2;
+    }
+    index
+}
+
+fn is_control(body: &[Tree]) -> bool {
+    body.first().map(|tree| tree.is("if") || tree.is("for")) == Some(true)
+}
+
+fn is_generic_start(previous: &Tree) -> bool {
+    match *previous {
+        Tree::Leaf(ref token) => token.is("::") || (token.kind == Ident && token.text.starts_with(char::is_uppercase)),
+        Tree::Group { .. } => false,
+    }
+}
+
+/*
+ * Check whether the braces at index in the body contain entries: the children of a widget, the
+ * branches of an if or a for, or the child properties, like `packing: { expand: true }`.
+ */
+fn is_block(body: &[Tree], index: usize) -> bool {
+    match body[index] {
+        Tree::Group { ref open, ref trees, .. } if open.text == "{" => {
+            if is_control(body) {
+                true
+            }
+            else if index == 2 && body[1].is(":") {
+                trees.iter().any(|tree| tree.is(":"))
+            }
+            else {
+                // A child, unlike a property or an event, has no `:` or `=>` before its braces.
+                !body[..index].iter().any(|tree| tree.is(":") || tree.is("=>"))
+            }
+        },
+        _ => false,
+    }
+}
+
+/*
+ * Only comments are left before the next comma or the end of the block.
+ */
+fn is_trailing_comment(trees: &[Tree]) -> bool {
+    trees[0].is_comment() && trees.iter().take_while(|tree| !tree.is(",")).all(Tree::is_comment)
+}
@@ -0,0 +1,134 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+/*
+ * Errors found while generating a widget are reported with compile_error!() instead of a panic.
+ * An error which prevents the generation is returned by the parser and the generator, while the
+ * other errors are collected with report_error(), so that they are all reported at once.
+ * The tokens parsed by syn do not have spans, so the errors point to the whole widget: they name the
+ * property, event or fragment they are about, and quote the unexpected token with show_token() when
+ * one was found.
+ */
+
+use std::mem;
+use std::sync::Mutex;
+
+use quote::{Tokens, ToTokens};
+use syn::TokenTree;
+
+lazy_static! {
+    static ref ERRORS: Mutex<Vec<String>> = Mutex::new(vec![]);
+}
+
+/*
+ * Call the generator, replacing its output by compile_error!() when it returned an error or when
+ * errors were reported.
+ */
+pub fn catch_errors<F: FnOnce() -> Result<Tokens, String>>(generate: F) -> Tokens {
+    ERRORS.lock().expect("lock errors").clear();
+    let result = generate();
+    let mut errors = mem::replace(&mut *ERRORS.lock().expect("lock errors"), vec![]);
+    match result {
+        Ok(tokens) => {
+            if errors.is_empty() {
+                return tokens;
+            }
+        },
+        Err(error) => errors.push(error),
+    }
+    let errors = errors.iter().map(|error| quote! {
+        compile_error!(#error);
+    });
+    quote! {
+        #(#errors)*
+    }
+}
+
+/*
+ * Suggest the closest of the expected names, if it is close enough to the found name.
+ */
+pub fn did_you_mean(found: &str, expected: &[&str]) -> String {
+    expected.iter()
+        .map(|name| (distance(found, name), name))
+        .filter(|&(distance, _)| distance <= 2)
+        .min()
+        .map(|(_, name)| format!(" (did you mean `{}`?)", name))
+        .unwrap_or_default()
+}
+
+/*
+ * Report an error without stopping the generation.
+ */
+pub fn report_error(message: String) {
+    ERRORS.lock().expect("lock errors").push(message);
+}
+
+/*
+ * Show a token as it is written in the source, instead of its debug representation.
+ */
+pub fn show_token(token: &TokenTree) -> String {
+    let mut tokens = Tokens::new();
+    token.to_tokens(&mut tokens);
+    tokens.to_string()
+}
+
+/*
+ * Levenshtein distance between two strings.
+ */
+fn distance(string1: &str, string2: &str) -> usize {
+    let chars2: Vec<_> = string2.chars().collect();
+    let mut previous: Vec<_> = (0..chars2.len() + 1).collect();
+    for (index1, char1) in string1.chars().enumerate() {
+        let mut current = vec![index1 + 1];
+        for (index2, &char2) in chars2.iter().enumerate() {
+            let substitution = previous[index2] + if char1 == char2 { 0 } else { 1 };
+            let value = substitution.min(previous[index2 + 1] + 1).min(current[index2] + 1);
+            current.push(value);
+        }
+        previous = current;
+    }
+    previous[chars2.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{catch_errors, did_you_mean, report_error};
+
+    #[test]
+    fn errors_are_reported_with_compile_error() {
+        let tokens = catch_errors(|| Ok(quote! { struct Widget; }));
+        assert_eq!(tokens.as_str(), "struct Widget ;");
+        let tokens = catch_errors(|| {
+            report_error("First error".to_string());
+            Err("Second error".to_string())
+        });
+        assert_eq!(tokens.as_str(), "compile_error ! ( \"First error\" ) ; compile_error ! ( \"Second error\" ) ;");
+        // The errors of the previous widget are not reported again.
+        let tokens = catch_errors(|| Ok(quote! { struct Widget; }));
+        assert_eq!(tokens.as_str(), "struct Widget ;");
+    }
+
+    #[test]
+    fn suggestions() {
+        assert_eq!(did_you_mean("contianer", &["container", "name", "parent"]), " (did you mean `container`?)");
+        assert_eq!(did_you_mean("size", &["container", "name", "parent"]), "");
+    }
+}
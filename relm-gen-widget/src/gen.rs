@@ -20,15 +20,18 @@
  */
 
 use std::collections::HashMap;
+use std::mem;
 
 use quote::Tokens;
 use syn::{Generics, Ident, Path, Ty, parse_path};
 
-use parser::{GtkWidget, RelmWidget, Widget};
-use parser::EventValue::{CurrentWidget, ForeignWidget};
+use adder::last_value_name;
+use diagnostics::report_error;
+use parser::{AsyncHandler, Condition, Event, EventValueReturn, GtkWidget, List, RelmWidget, Widget};
+use parser::EventValue::{Async, CurrentWidget, ForeignWidget};
 use parser::EventValueReturn::{CallReturn, Return, WithoutReturn};
 use parser::EitherWidget::{Gtk, Relm};
-use super::{Driver, get_generic_types};
+use super::{Driver, gen_setter_args, gen_update_method_name, get_generic_types};
 
 use self::WidgetType::*;
 
@@ -39,8 +42,9 @@ macro_rules! gen_set_prop_calls {
         let mut visible_properties = vec![];
         for (key, value) in &$widget.properties {
             let property_func = Ident::new(format!("set_{}", key));
+            let args = gen_setter_args(value.as_str());
             let property = quote! {
-                #ident.#property_func(#value);
+                #ident.#property_func(#args);
             };
             if key == "visible" {
                 visible_properties.push(property);
@@ -64,7 +68,16 @@ macro_rules! set_container {
                     else {
                         "#[container]".to_string()
                     };
-                panic!("Cannot use the {} attribute twice in the same widget", attribute);
+                let suggestion =
+                    if container_type.is_none() {
+                        ": use #[container=\"name\"] for the other containers, which receive the \
+                         children whose parent_id() is name"
+                    }
+                    else {
+                        ""
+                    };
+                report_error(format!("Cannot use the {} attribute twice in the same widget{}", attribute,
+                    suggestion));
             }
             $_self.relm_widgets.insert($widget_name.clone(), $widget_type.clone());
             $_self.container_names.insert(container_type.clone(), ($widget_name.clone(), $widget_type.clone()));
@@ -93,6 +106,18 @@ pub fn gen(name: &Ident, typ: &Ty, widget: &Widget, driver: &mut Driver) -> (Tok
     let widget_names2 = widget_names1;
     let events = &generator.events;
     let phantom_field = gen_phantom_field(typ);
+    // The remote is used by the methods updating the conditions and lists, by the setters of the props
+    // and to get the fields updated by the bindings.
+    if !generator.dynamic_fields.is_empty() || !driver.props.is_empty() || !driver.bound_fields.is_empty() {
+        generator.dynamic_fields.push((Ident::new("__relm_remote"), quote! { ::relm::RemoteRelm<#typ> },
+            quote! { relm.clone() }));
+    }
+    for name in &driver.last_values {
+        generator.dynamic_fields.push((name.clone(), quote! { ::relm::LastValue },
+            quote! { ::relm::LastValue::default() }));
+    }
+    let dynamic_names: Vec<_> = generator.dynamic_fields.iter().map(|&(ref name, _, _)| name).collect();
+    let dynamic_inits: Vec<_> = generator.dynamic_fields.iter().map(|&(_, _, ref init)| init).collect();
     let code = quote! {
         #widget_tokens
 
@@ -101,14 +126,22 @@ pub fn gen(name: &Ident, typ: &Ty, widget: &Widget, driver: &mut Driver) -> (Tok
         #name {
             #root_widget_name: #root_widget_name,
             #(#widget_names1: #widget_names2,)*
+            #(#dynamic_names: #dynamic_inits,)*
             #phantom_field
         }
     };
+    driver.dynamic_fields = generator.dynamic_fields.iter()
+        .map(|&(ref name, ref typ, _)| (name.clone(), typ.clone()))
+        .collect();
+    driver.dynamic_methods = generator.dynamic_methods.clone();
     let container_impl = gen_container_impl(&generator, widget, driver.generic_types.as_ref().expect("generic types"));
     (code, generator.relm_widgets, container_impl)
 }
 
 struct Generator<'a> {
+    // Fields added to the struct for the conditions and lists: name, type and initial value.
+    dynamic_fields: Vec<(Ident, Tokens, Tokens)>,
+    dynamic_methods: Vec<Tokens>,
     container_names: HashMap<Option<String>, (Ident, Path)>,
     driver: Option<&'a mut Driver>,
     events: Vec<Tokens>,
@@ -119,6 +152,8 @@ struct Generator<'a> {
 impl<'a> Generator<'a> {
     fn new(driver: &'a mut Driver) -> Self {
         Generator {
+            dynamic_fields: vec![],
+            dynamic_methods: vec![],
             container_names: HashMap::new(),
             driver: Some(driver),
             events: vec![],
@@ -132,7 +167,21 @@ impl<'a> Generator<'a> {
     {
         let widget_name = &widget.name;
         if let Some(name) = parent {
-            if parent_widget_type == IsGtk {
+            let parent_widget =
+                if parent_widget_type == IsGtk {
+                    quote! { &#name }
+                }
+                else {
+                    quote! { #name.widget().root() }
+                };
+            if let Some(popup) = popup_kind(widget) {
+                // Menus and popovers are attached to their parent instead of being added to it.
+                let attach = Ident::new(format!("attach_{}", popup));
+                quote! {
+                    ::relm::#attach(#parent_widget, &#widget_name);
+                }
+            }
+            else if parent_widget_type == IsGtk {
                 quote! {
                     ::gtk::ContainerExt::add(&#name, &#widget_name);
                 }
@@ -197,6 +246,61 @@ impl<'a> Generator<'a> {
         }
     }
 
+    /*
+     * Connect the signal emitted when a bound property is changed by the user, to update the model.
+     */
+    fn collect_bindings(&mut self, widget: &Widget, gtk_widget: &GtkWidget) {
+        let widget_name = &widget.name;
+        let is_switch = widget.typ.segments.last().map(|segment| segment.ident == "Switch") == Some(true);
+        for binding in &gtk_widget.bindings {
+            let (signal, params, value, return_value) =
+                match binding.property.as_ref() {
+                    "text" => ("changed", quote! { widget }, quote! { widget.get_text().unwrap_or_default() },
+                        quote! {}),
+                    "active" if is_switch => ("state_set", quote! { _, state }, quote! { state },
+                        quote! { ::gtk::Inhibit(false) }),
+                    "active" => ("toggled", quote! { widget }, quote! { widget.get_active() }, quote! {}),
+                    "value" => ("value_changed", quote! { widget }, quote! { widget.get_value() }, quote! {}),
+                    _ => unreachable!("the parser checks the bound property"),
+                };
+            let connect = Ident::new(format!("connect_{}", signal));
+            let model_path = &binding.model_path;
+            // The field is given to update(), which sets the other properties using it.
+            let field = model_path.as_str();
+            {
+                let driver = self.driver.as_mut().expect("driver");
+                driver.bound_fields.push(model_path.clone());
+                // The user changes the property without calling the setter, so its last value is unknown.
+                let last_value = last_value_name(widget_name, &binding.property);
+                driver.last_values.retain(|ident| *ident != last_value);
+            }
+            let update = quote! {
+                relm.update_bound_model(#field, move |model| #model_path = value)
+            };
+            let update =
+                match binding.value {
+                    Some(ref value) => quote! {
+                        if #update {
+                            relm.stream().emit(#value);
+                        }
+                    },
+                    None => quote! {
+                        let _ = #update;
+                    },
+                };
+            self.events.push(quote! {
+                {
+                    let relm = relm.clone();
+                    #widget_name.#connect(move |#params| {
+                        let value = #value;
+                        #update
+                        #return_value
+                    });
+                }
+            });
+        }
+    }
+
     fn collect_events(&mut self, widget: &Widget, gtk_widget: &GtkWidget) {
         let widget_name = &widget.name;
         for (name, event) in &gtk_widget.events {
@@ -214,6 +318,17 @@ impl<'a> Generator<'a> {
                 };
             let connect =
                 match event.value {
+                    Async(ref handler) => {
+                        let spawn = gen_async_handler(handler);
+                        quote! {
+                            {
+                                let relm = relm.clone();
+                                #widget_name.#event_ident(move |#(#event_params),*| {
+                                    #spawn
+                                });
+                            }
+                        }
+                    },
                     CurrentWidget(WithoutReturn(ref event_value)) => quote! {
                         connect!(relm, #widget_name, #event_ident(#(#event_params),*), #event_value);
                     },
@@ -223,9 +338,28 @@ impl<'a> Generator<'a> {
                     CurrentWidget(Return(ref event_value, ref return_value)) => quote! {
                         connect!(relm, #widget_name, #event_ident(#(#event_params),*) (#event_value, #return_value));
                     },
-                    ForeignWidget(_, Return(_, _)) | ForeignWidget(_, CallReturn(_)) => unreachable!(),
+                    ForeignWidget(ref foreign_widget_name, ref event_value) => {
+                        // Send the message to the other widget and return the value to the GTK+ callback.
+                        let model = gen_handler_model(event);
+                        let handler = gen_reply_handler(event_value, event);
+                        quote! {
+                            {
+                                #model
+                                let stream = #foreign_widget_name.stream().clone();
+                                #widget_name.#event_ident(move |#(#event_params),*| {
+                                    #handler
+                                    return_value
+                                });
+                            }
+                        }
+                    },
+                    // The relm variable is available in the expression, so that it can ask for a reply with
+                    // emit_with_reply().
                     CurrentWidget(CallReturn(ref func)) => quote! {
-                        connect!(relm, #widget_name, #event_ident(#(#event_params),*) #event_model_ident #func);
+                        {
+                            let relm = relm.clone();
+                            connect!(relm, #widget_name, #event_ident(#(#event_params),*) #event_model_ident #func);
+                        }
                     },
 
                 };
@@ -233,6 +367,91 @@ impl<'a> Generator<'a> {
         }
     }
 
+    /*
+     * Connect the signals given by name, like `"notify::position"`, which have no connect_*() method.
+     */
+    fn collect_signals(&mut self, widget: &Widget, gtk_widget: &GtkWidget) {
+        let widget_name = &widget.name;
+        for signal in &gtk_widget.signals {
+            let signal_name = &signal.name;
+            let event = &signal.event;
+            let params = event.params.iter().zip(&signal.param_types).enumerate()
+                .map(|(index, (param, typ))| {
+                    let param = Ident::new(param.as_ref());
+                    match *typ {
+                        Some(ref typ) => {
+                            let message = format!("Parameter {} of signal \"{}\" is not a {}", index, signal_name,
+                                typ.as_str().replace(" :: ", "::"));
+                            quote! {
+                                let #param = values[#index].get::<#typ>().expect(#message);
+                            }
+                        },
+                        None => quote! {
+                            let #param = &values[#index];
+                        },
+                    }
+                });
+            let values =
+                if event.params.is_empty() {
+                    quote! { _ }
+                }
+                else {
+                    quote! { values }
+                };
+            if let Async(ref handler) = event.value {
+                let spawn = gen_async_handler(handler);
+                self.events.push(quote! {
+                    {
+                        let relm = relm.clone();
+                        let result = ::relm::gtk_ext::connect_signal(&#widget_name, #signal_name, move |#values| {
+                            #(#params)*
+                            #spawn
+                            None
+                        });
+                        if let Err(error) = result {
+                            ::relm::gtk_ext::warn_error(&error);
+                        }
+                    }
+                });
+                continue;
+            }
+            let (stream, event_value) =
+                match event.value {
+                    Async(_) => unreachable!("async handlers are generated above"),
+                    CurrentWidget(ref event_value) => (quote! { relm }, event_value),
+                    ForeignWidget(ref foreign_widget_name, ref event_value) => (quote! { #foreign_widget_name }, event_value),
+                };
+            let (model, handler, return_value) =
+                match *event_value {
+                    WithoutReturn(ref msg) => (quote! {}, quote! { stream.emit(#msg); }, quote! { None }),
+                    _ => (gen_handler_model(event), gen_reply_handler(event_value, event),
+                        quote! { Some(::relm::gtk_ext::PropertyValue::to_property_value(&return_value)) }),
+                };
+            // The relm variable is available in the expression, so that it can ask for a reply with
+            // emit_with_reply().
+            let clone_relm =
+                match event.value {
+                    CurrentWidget(CallReturn(_)) => quote! { let relm = relm.clone(); },
+                    _ => quote! {},
+                };
+            self.events.push(quote! {
+                {
+                    #clone_relm
+                    #model
+                    let stream = #stream.stream().clone();
+                    let result = ::relm::gtk_ext::connect_signal(&#widget_name, #signal_name, move |#values| {
+                        #(#params)*
+                        #handler
+                        #return_value
+                    });
+                    if let Err(error) = result {
+                        ::relm::gtk_ext::warn_error(&error);
+                    }
+                }
+            });
+        }
+    }
+
     fn collect_relm_events(&mut self, widget: &Widget, relm_widget: &RelmWidget) {
         let widget_name = &widget.name;
         for (name, widget_events) in &relm_widget.events {
@@ -252,20 +471,320 @@ impl<'a> Generator<'a> {
                     };
                 let connect =
                     match event.value {
+                        Async(ref handler) => {
+                            let spawn = gen_async_handler(handler);
+                            quote! {
+                                {
+                                    let relm = relm.clone();
+                                    #widget_name.stream().observe(move |msg| {
+                                        #[allow(unreachable_patterns)]
+                                        match msg {
+                                            #event_ident #params => {
+                                                #spawn
+                                            },
+                                            _ => (),
+                                        }
+                                    });
+                                }
+                            }
+                        },
                         CurrentWidget(WithoutReturn(ref event_value)) => quote! {
                             connect!(#widget_name@#event_ident #params, relm, #event_value);
                         },
                         ForeignWidget(ref foreign_widget_name, WithoutReturn(ref event_value)) => quote! {
                             connect!(#widget_name@#event_ident #params, #foreign_widget_name, #event_value);
                         },
-                        CurrentWidget(Return(_, _)) | CurrentWidget(CallReturn(_)) | ForeignWidget(_, Return(_, _)) |
-                            ForeignWidget(_, CallReturn(_)) => unreachable!(),
+                        // The child waits for the value returned here when it emits the message with
+                        // emit_with_reply().
+                        CurrentWidget(ref event_value) | ForeignWidget(_, ref event_value) => {
+                            let stream =
+                                if let ForeignWidget(ref foreign_widget_name, _) = event.value {
+                                    quote! { #foreign_widget_name.stream().clone() }
+                                }
+                                else {
+                                    quote! { relm.stream().clone() }
+                                };
+                            let model = gen_handler_model(event);
+                            let handler = gen_relm_reply_handler(event_value, event);
+                            quote! {
+                                {
+                                    #model
+                                    let stream = #stream;
+                                    #widget_name.stream().observe_with_reply(move |msg| {
+                                        #[allow(unreachable_patterns)]
+                                        match msg {
+                                            #event_ident #params => {
+                                                #handler
+                                            },
+                                            _ => None,
+                                        }
+                                    });
+                                }
+                            }
+                        },
                     };
                 self.events.push(connect);
             }
         }
     }
 
+    fn gen_actions(&mut self, widget: &Widget, gtk_widget: &GtkWidget) -> Tokens {
+        let widget_name = &widget.name;
+        let mut tokens = Tokens::new();
+        let mut groups = HashMap::new();
+        for action in &gtk_widget.actions {
+            let prefix = &action.prefix;
+            let group: &Ident = groups.entry(prefix.clone()).or_insert_with(|| {
+                let group = Ident::new(format!("__relm_{}_{}_actions", widget_name, prefix));
+                tokens.append(quote! {
+                    let #group = ::relm::ActionGroup::new(&#widget_name, #prefix);
+                });
+                group
+            });
+            let name = &action.name;
+            let action_name = &action.action_name;
+            let value = &action.value;
+            self.widget_names.push(name.clone());
+            tokens.append(quote! {
+                let #name = {
+                    let stream = relm.stream().clone();
+                    #group.add(#action_name, move || stream.emit(#value))
+                };
+            });
+            let ident = quote! { #name };
+            let (properties, _) = gen_set_prop_calls!(action, ident);
+            tokens.append_all(properties);
+            if let Some(ref accelerator) = action.accel {
+                tokens.append(quote! {
+                    {
+                        let action = #name.clone();
+                        ::relm::connect_accel(&#widget_name, #accelerator, move || action.activate());
+                    }
+                });
+            }
+        }
+        for accel in &gtk_widget.accels {
+            let accelerator = &accel.accelerator;
+            let value = &accel.value;
+            tokens.append(quote! {
+                {
+                    let stream = relm.stream().clone();
+                    ::relm::connect_accel(&#widget_name, #accelerator, move || stream.emit(#value));
+                }
+            });
+        }
+        tokens
+    }
+
+    /*
+     * Generate the code creating the widgets of a branch, including its events.
+     */
+    fn gen_branch(&mut self, widget: &Widget, placeholder: &Ident) -> (Tokens, Vec<Tokens>) {
+        let events = mem::replace(&mut self.events, vec![]);
+        let widget_names = mem::replace(&mut self.widget_names, vec![]);
+        let relm_widgets = mem::replace(&mut self.relm_widgets, HashMap::new());
+        let code = self.widget(widget, Some(placeholder), IsGtk);
+        let branch_events = mem::replace(&mut self.events, events);
+        self.widget_names = widget_names;
+        self.relm_widgets = relm_widgets;
+        (code, branch_events)
+    }
+
+    /*
+     * The widgets of the active branch are created in the placeholder and saved in Option fields.
+     * A method destroys them and creates the widgets of the new branch when the value of the
+     * condition changes.
+     * The fields are shared by the copies of the widget, so that the one kept in the component
+     * sees the widgets created by the one running update().
+     */
+    fn gen_condition(&mut self, widget: &Widget, condition: &Condition) -> Tokens {
+        let placeholder = &widget.name;
+        let value_name = Ident::new(format!("{}_value", placeholder));
+        let method = gen_update_method_name(placeholder);
+        let mut fields = vec![];
+        let mut branches = vec![];
+        for &(_, ref branch) in &condition.branches {
+            let start = fields.len();
+            get_branch_fields(branch, false, &mut fields);
+            let (code, events) = self.gen_branch(branch, placeholder);
+            let code = quote! {
+                #code
+                #(#events)*
+            };
+            branches.push((code, start, fields.len()));
+        }
+        let field_names: Vec<_> = fields.iter().map(|&(ref name, _, _)| name.clone()).collect();
+        let field_names = &field_names;
+        // The relm widgets of the old branch are stopped, except the children of a relm widget which
+        // are stopped by their parent.
+        let stopped_names: Vec<_> = fields.iter()
+            .filter(|&&(_, _, stop)| stop)
+            .map(|&(ref name, _, _)| name)
+            .collect();
+        let arms: Vec<_> = branches.iter().enumerate().map(|(index, &(ref code, start, end))| {
+            let values = field_names.iter().enumerate().map(|(field_index, name)| {
+                if field_index >= start && field_index < end {
+                    quote! { Some(#name) }
+                }
+                else {
+                    quote! { None }
+                }
+            });
+            quote! {
+                #index => {
+                    #code
+                    (#(#values,)*)
+                },
+            }
+        }).collect();
+        let nones = field_names.iter().map(|_| quote! { None });
+        let create_branch = quote! {
+            match #value_name {
+                #(#arms)*
+                _ => (#(#nones,)*),
+            }
+        };
+
+        let mut value = Tokens::new();
+        for (index, &(ref expr, _)) in condition.branches.iter().enumerate() {
+            match *expr {
+                Some(ref expr) => value.append(quote! { if #expr { #index } else }),
+                None => value.append(quote! { { #index } }),
+            }
+        }
+        if let Some(&(Some(_), _)) = condition.branches.last() {
+            let index = condition.branches.len();
+            value.append(quote! { { #index } });
+        }
+
+        let field_names2 = field_names;
+        self.dynamic_methods.push(quote! {
+            #[allow(unused_variables)]
+            fn #method(&mut self, model: &<Self as ::relm::Widget>::Model) {
+                let #value_name: usize = #value;
+                if #value_name != self.#value_name.get() {
+                    self.#value_name.set(#value_name);
+                    #(
+                        if let Some(component) = self.#stopped_names.borrow_mut().take() {
+                            component.stop();
+                        }
+                    )*
+                    for child in ::gtk::ContainerExt::get_children(&self.#placeholder) {
+                        ::gtk::WidgetExt::destroy(&child);
+                    }
+                    let relm = &self.__relm_remote;
+                    let #placeholder = self.#placeholder.clone();
+                    let (#(#field_names,)*) = #create_branch;
+                    #(*self.#field_names.borrow_mut() = #field_names2;)*
+                }
+            }
+        });
+        self.dynamic_fields.push((value_name.clone(), quote! { ::std::rc::Rc<::std::cell::Cell<usize>> },
+            quote! { ::std::rc::Rc::new(::std::cell::Cell::new(#value_name)) }));
+        for (name, typ, _) in fields {
+            self.dynamic_fields.push((name.clone(), quote! { ::std::rc::Rc<::std::cell::RefCell<Option<#typ>>> },
+                quote! { ::std::rc::Rc::new(::std::cell::RefCell::new(#name)) }));
+        }
+
+        quote! {
+            let #value_name: usize = #value;
+            let (#(#field_names,)*) = #create_branch;
+        }
+    }
+
+    /*
+     * Generate the code creating the children of a widget.
+     * The widgets of a list are put after the static children and the widgets of the lists preceding
+     * it.
+     */
+    fn children(&mut self, widget: &Widget, parent_widget_type: WidgetType) -> Vec<Tokens> {
+        let mut children = vec![];
+        let mut static_count = 0i32;
+        let mut previous_lists = vec![];
+        for child in &widget.children {
+            if let Some(ref list) = child.list {
+                children.push(self.gen_list(child, list, &widget.name, parent_widget_type, static_count,
+                    &previous_lists));
+                previous_lists.push(gen_list_children_name(&child.name));
+            }
+            else {
+                children.push(self.widget(child, Some(&widget.name), parent_widget_type));
+                // Menus and popovers are not children of their parent.
+                if popup_kind(child).is_none() {
+                    static_count += 1;
+                }
+            }
+        }
+        children
+    }
+
+    /*
+     * The widgets of a list are added to the parent and saved in a KeyedList, which is updated by a
+     * method when the model variables used by the items change.
+     * The parent is saved as a gtk::Container in the placeholder field; for a relm parent, it is its
+     * containing widget.
+     */
+    fn gen_list(&mut self, widget: &Widget, list: &List, parent: &Ident, parent_widget_type: WidgetType,
+        static_count: i32, previous_lists: &[Ident]) -> Tokens
+    {
+        let placeholder = &widget.name;
+        self.widget_names.push(placeholder.clone());
+        let children_name = gen_list_children_name(placeholder);
+        let method = gen_update_method_name(placeholder);
+        let container =
+            if parent_widget_type == IsGtk {
+                quote! { ::std::clone::Clone::clone(&#parent) }
+            }
+            else {
+                quote! { ::std::clone::Clone::clone(::relm::Container::container(#parent.widget())) }
+            };
+        let previous_lists2 = previous_lists;
+        let child = &list.widget;
+        let child_name = &child.name;
+        let child_type = &child.typ;
+        let item = &list.item;
+        let items = &list.items;
+        let key = &list.key;
+        let model_param = gen_model_param(&child.init_parameters);
+        let (create, events) = self.gen_branch(child, placeholder);
+        // The event handlers outlive the item, so they get their own copy.
+        let events = events.iter().map(|event| quote! {
+            {
+                let #item = ::std::clone::Clone::clone(#item);
+                #event
+            }
+        });
+        let update = quote! {
+            let offset = #static_count #(+ #previous_lists.len() as i32)*;
+            #children_name.update(&#placeholder, offset, &#items, |#item| #key, |#item| #model_param, |#item| {
+                #create
+                #(#events)*
+                #child_name
+            });
+        };
+
+        self.dynamic_methods.push(quote! {
+            #[allow(unused_variables)]
+            fn #method(&mut self, model: &<Self as ::relm::Widget>::Model) {
+                let relm = &self.__relm_remote;
+                let #placeholder = self.#placeholder.clone();
+                let #children_name = &self.#children_name;
+                #(let #previous_lists = &self.#previous_lists2;)*
+                #update
+            }
+        });
+        self.dynamic_fields.push((children_name.clone(), quote! { ::relm::KeyedList<#child_type> },
+            quote! { #children_name }));
+
+        quote! {
+            let #placeholder: ::gtk::Container = ::relm::Cast::upcast(#container);
+            let #children_name = ::relm::KeyedList::new();
+            {
+                #update
+            }
+        }
+    }
+
     fn gtk_widget(&mut self, widget: &Widget, gtk_widget: &GtkWidget, parent: Option<&Ident>,
         parent_widget_type: WidgetType) -> Tokens
     {
@@ -278,24 +797,96 @@ impl<'a> Generator<'a> {
             self.relm_widgets.insert(widget_name.clone(), struct_name.clone());
         }
 
-        let construct_widget = gen_construct_widget(widget);
+        /* An unknown internal child is replaced by a new widget which is not in the parent. */
+        let construct_widget =
+            match (&gtk_widget.internal_child, parent) {
+                (&Some(ref child_name), Some(parent)) => {
+                    let new_widget = gen_construct_widget(widget);
+                    quote! {
+                        match ::relm::gtk_ext::get_internal_child(&#parent, #child_name) {
+                            Ok(child) => unsafe {
+                                use relm::Downcast;
+                                child.downcast_unchecked()
+                            },
+                            Err(error) => {
+                                ::relm::gtk_ext::warn_error(&error);
+                                #new_widget
+                            },
+                        }
+                    }
+                },
+                _ => gen_construct_widget(widget),
+            };
         self.collect_events(widget, gtk_widget);
+        self.collect_signals(widget, gtk_widget);
+        self.collect_bindings(widget, gtk_widget);
 
-        let children: Vec<_> = widget.children.iter()
-            .map(|child| self.widget(child, Some(widget_name), IsGtk))
-            .collect();
+        let children = self.children(widget, IsGtk);
 
-        let add_child_or_show_all = self.add_child_or_show_all(widget, parent, parent_widget_type);
+        // An internal child is already in its parent.
+        let add_child_or_show_all =
+            if gtk_widget.internal_child.is_some() {
+                quote! {}
+            }
+            else {
+                self.add_child_or_show_all(widget, parent, parent_widget_type)
+            };
         let ident = quote! { #widget_name };
-        let (properties, visible_properties) = gen_set_prop_calls!(widget, ident);
-        let child_properties = gen_set_child_prop_calls(widget, parent, parent_widget_type, IsGtk);
+        // The properties of the glade file are set first, so that the view! macro can change them.
+        let mut properties: Vec<_> = gtk_widget.glade_properties.iter().map(|&(ref name, ref value)| {
+            let name = name.replace('_', "-");
+            quote! {
+                if let Err(error) = ::relm::gtk_ext::set_property_from_string(&#widget_name, #name, #value) {
+                    ::relm::gtk_ext::warn_error(&error);
+                }
+            }
+        }).collect();
+        let (typed_properties, visible_properties) = gen_set_prop_calls!(widget, ident);
+        properties.extend(typed_properties);
+        if let Some(response) = gtk_widget.response {
+            properties.push(quote! {
+                ::relm::gtk_ext::set_response(&::relm::Cast::upcast(#widget_name.clone()), #response);
+            });
+        }
+        let mut child_properties = vec![];
+        if let Some(parent) = parent {
+            child_properties.extend(gtk_widget.glade_child_properties.iter().map(|&(ref name, ref value)| {
+                let name = name.replace('_', "-");
+                quote! {
+                    let result = ::relm::gtk_ext::set_child_property_from_string(&#parent, &#widget_name, #name,
+                        #value);
+                    if let Err(error) = result {
+                        ::relm::gtk_ext::warn_error(&error);
+                    }
+                }
+            }));
+        }
+        child_properties.extend(gen_set_child_prop_calls(widget, parent, parent_widget_type, IsGtk));
+        let actions = self.gen_actions(widget, gtk_widget);
+        let condition =
+            match widget.condition {
+                Some(ref condition) => self.gen_condition(widget, condition),
+                None => quote! {},
+            };
+        // Menus and popovers are only shown when they pop up.
+        let show =
+            if popup_kind(widget).is_some() {
+                quote! {}
+            }
+            else {
+                quote! {
+                    #widget_name.show();
+                }
+            };
 
         quote! {
             let #widget_name: #struct_name = #construct_widget;
             #(#properties)*
+            #actions
+            #condition
             #(#children)*
             #add_child_or_show_all
-            #widget_name.show();
+            #show
             #(#visible_properties)*
             #(#child_properties)*
         }
@@ -313,9 +904,7 @@ impl<'a> Generator<'a> {
 
         self.collect_relm_events(widget, relm_widget);
 
-        let children: Vec<_> = widget.children.iter()
-            .map(|child| self.widget(child, Some(widget_name), IsRelm))
-            .collect();
+        let children = self.children(widget, IsRelm);
         let ident = quote! { #widget_name.widget() };
         let (properties, visible_properties) = gen_set_prop_calls!(widget, ident);
 
@@ -340,18 +929,75 @@ impl<'a> Generator<'a> {
     }
 }
 
+/*
+ * Get the name of the field saving the KeyedList of the list in the placeholder.
+ */
+fn gen_list_children_name(placeholder: &Ident) -> Ident {
+    Ident::new(format!("{}_children", placeholder))
+}
+
+/*
+ * Get the fields saving the widgets of a branch, with whether the widget is a relm widget to stop
+ * when the branch is destroyed.
+ * The parser checks that there is no condition, for or container in the branch.
+ */
+fn get_branch_fields(widget: &Widget, parent_is_relm: bool, fields: &mut Vec<(Ident, Tokens, bool)>) {
+    let (typ, is_relm) =
+        match widget.widget {
+            Gtk(_) => {
+                let typ = &widget.typ;
+                (quote! { #typ }, false)
+            },
+            Relm(_) => {
+                let typ = gen_relm_component_type(&widget.typ);
+                (quote! { #typ }, true)
+            },
+        };
+    fields.push((widget.name.clone(), typ, is_relm && !parent_is_relm));
+    for child in &widget.children {
+        get_branch_fields(child, is_relm, fields);
+    }
+}
+
 fn gen_construct_widget(widget: &Widget) -> Tokens {
     let struct_name = &widget.typ;
 
     let params = &widget.init_parameters;
 
+    if let Gtk(ref gtk_widget) = widget.widget {
+        if !gtk_widget.construct_properties.is_empty() {
+            let properties = gtk_widget.construct_properties.iter().map(|&(ref name, ref value)| {
+                let name = name.replace('_', "-");
+                quote! {
+                    (#name, &(#value) as &::relm::gtk_ext::PropertyValue)
+                }
+            });
+            let new_widget = gen_new_widget(struct_name);
+            /* The widget is created without its construct properties when one of them is invalid. */
+            return quote! {
+                unsafe {
+                    use gtk::StaticType;
+                    use relm::Downcast;
+                    let widget =
+                        match ::relm::gtk_ext::new_widget(#struct_name::static_type(), &[#(#properties),*]) {
+                            Ok(widget) => widget,
+                            Err(error) => {
+                                ::relm::gtk_ext::warn_error(&error);
+                                #new_widget
+                            },
+                        };
+                    widget.downcast_unchecked()
+                }
+            };
+        }
+    }
+
     if widget.init_parameters.is_empty() {
+        let new_widget = gen_new_widget(struct_name);
         quote! {
             unsafe {
-                use gtk::StaticType;
-                use relm::{Downcast, FromGlibPtrNone, ToGlib};
-                ::gtk::Widget::from_glib_none(::relm::g_object_new(#struct_name::static_type().to_glib(),
-                #(#params,)* ::std::ptr::null() as *const i8) as *mut _)
+                use relm::Downcast;
+                #new_widget
                 .downcast_unchecked()
             }
         }
@@ -363,6 +1009,20 @@ fn gen_construct_widget(widget: &Widget) -> Tokens {
     }
 }
 
+/*
+ * Create a gtk::Widget of type struct_name without properties.
+ */
+fn gen_new_widget(struct_name: &Path) -> Tokens {
+    quote! {
+        {
+            use gtk::StaticType;
+            use relm::{FromGlibPtrNone, ToGlib};
+            ::gtk::Widget::from_glib_none(::relm::g_object_new(#struct_name::static_type().to_glib(),
+                ::std::ptr::null() as *const i8) as *mut _)
+        }
+    }
+}
+
 fn gen_widget_type(widget: &Widget) -> Tokens {
     match widget.widget {
         Gtk(ref gtk_widget) => {
@@ -386,42 +1046,39 @@ fn gen_add_widget_method(container_names: &HashMap<Option<String>, (Ident, Path)
         let mut other_containers = Tokens::new();
         for (parent_id, &(ref name, ref typ)) in container_names {
             let first_type_part = &typ.segments.first().expect("first segment").ident;
-            let (container_trait, upcast_container) =
+            // A relm container receives the placement too, so that it can route the widget itself.
+            let add_child =
                 if first_type_part == "gtk" {
-                    (quote! {
-                        ::gtk::ContainerExt
-                    }, quote! {
+                    quote! {
+                        ::relm::add_child(&self.#name, widget.root(), placement.position);
                         ::relm::Cast::upcast(self.#name.clone())
-                    })
+                    }
                 }
                 else {
-                    (quote! {
-                        ::relm::RelmContainer
-                    }, quote! {
+                    quote! {
+                        ::relm::Container::add_widget_at(self.#name.widget(), widget, placement);
                         ::relm::Cast::upcast(self.#name.widget().root().clone())
-                    })
+                    }
                 };
             if parent_id.is_none() {
-                default_container = quote! {
-                    #container_trait::add(&self.#name, widget.root());
-                    #upcast_container
-                };
+                default_container = add_child;
             }
             else {
+                let condition = quote! {
+                    placement.parent_id.as_ref().map(|id| id.as_str()) == Some(#parent_id)
+                };
                 if other_containers.as_str().is_empty() {
                     other_containers = quote! {
-                        if WIDGET::parent_id() == Some(#parent_id) {
-                            #container_trait::add(&self.#name, widget.root());
-                            #upcast_container
+                        if #condition {
+                            #add_child
                         }
                     };
                 }
                 else {
                     other_containers = quote! {
                         #other_containers
-                        else if WIDGET::parent_id() == Some(#parent_id) {
-                            #container_trait::add(&self.#name, widget.root());
-                            #upcast_container
+                        else if #condition {
+                            #add_child
                         }
                     };
                 }
@@ -435,7 +1092,9 @@ fn gen_add_widget_method(container_names: &HashMap<Option<String>, (Ident, Path)
             };
         }
         quote! {
-            fn add_widget<WIDGET: Widget>(&self, widget: &WIDGET) -> ::gtk::Container {
+            fn add_widget_at<WIDGET: Widget>(&self, widget: &WIDGET, placement: &::relm::Placement)
+                -> ::gtk::Container
+            {
                 #other_containers
                 #default_container
             }
@@ -454,7 +1113,10 @@ fn gen_container_impl(generator: &Generator, widget: &Widget, generic_types: &Ge
         }
     }
     else if !generator.container_names.contains_key(&None) {
-        panic!("Use of #[container=\"name\"] attribute without the default #[container].");
+        report_error("Use of #[container=\"name\"] attribute without the default #[container]: add \
+                     #[container] to the widget receiving the children without a parent_id".to_string());
+        quote! {
+        }
     }
     else {
         let mut container_type = None;
@@ -466,9 +1128,10 @@ fn gen_container_impl(generator: &Generator, widget: &Widget, generic_types: &Ge
         let typ = container_type.expect("container type");
         let &(ref name, _) = generator.container_names.get(&None).expect("default container");
         let add_widget_method = gen_add_widget_method(&generator.container_names);
+        let where_clause = &generic_types.where_clause;
 
         quote! {
-            impl #generic_types ::relm::Container for #widget_type {
+            impl #generic_types ::relm::Container for #widget_type #where_clause {
                 type Container = #typ;
 
                 fn container(&self) -> &Self::Container {
@@ -505,6 +1168,120 @@ fn gen_phantom_field(typ: &Ty) -> Tokens {
     }
 }
 
+fn popup_kind(widget: &Widget) -> Option<&'static str> {
+    let segments = &widget.typ.segments;
+    if segments.len() == 2 && segments[0].ident == "gtk" {
+        match segments[1].ident.as_ref() {
+            "Menu" => return Some("menu"),
+            "Popover" => return Some("popover"),
+            _ => (),
+        }
+    }
+    None
+}
+
+/*
+ * Spawn the future of an `async` handler on the tokio thread, with a clone of relm.
+ */
+fn gen_async_handler(handler: &AsyncHandler) -> Tokens {
+    let handle =
+        match handler.params.len() {
+            0 => Ident::new("_"),
+            1 => Ident::new(handler.params[0].as_ref()),
+            _ => unreachable!("the parser checks the parameters of async"),
+        };
+    let body = &handler.body;
+    let success = &handler.success;
+    match handler.failure {
+        Some(ref failure) => quote! {
+            relm.connect_exec(move |#handle: &::relm::Handle| #body, #success, #failure);
+        },
+        None => quote! {
+            relm.connect_exec_ignore_err(move |#handle: &::relm::Handle| #body, #success);
+        },
+    }
+}
+
+/*
+ * Clone the model for the events using `with model`, to lock it in the handler.
+ */
+fn gen_handler_model(event: &Event) -> Tokens {
+    match event.model_ident {
+        Some(ref model) => quote! {
+            let #model = relm.model().clone();
+        },
+        None => quote! {
+        },
+    }
+}
+
+/*
+ * Send the message of a `(Msg, value)` or `return expr` handler of a GTK+ signal to the stream and
+ * bind the value to return_value.
+ */
+fn gen_reply_handler(event_value: &EventValueReturn, event: &Event) -> Tokens {
+    let lock_model =
+        match event.model_ident {
+            Some(ref model) => quote! {
+                let #model = &mut *#model.lock().unwrap();
+            },
+            None => quote! {
+            },
+        };
+    let value = gen_reply_value(event_value);
+    quote! {
+        #lock_model
+        let (msg, return_value) = #value;
+        let msg: Option<_> = msg.into();
+        if let Some(msg) = msg {
+            stream.emit(msg);
+        }
+    }
+}
+
+/*
+ * Send the message of a `(Msg, value)` or `return expr` handler of a relm widget to the stream and
+ * evaluate to the reply.
+ * With `with model`, the model is only locked when it is free: when the parent emits to this child
+ * from its update(), which holds the lock, the child gets no reply instead of a deadlock.
+ */
+fn gen_relm_reply_handler(event_value: &EventValueReturn, event: &Event) -> Tokens {
+    let value = gen_reply_value(event_value);
+    let reply =
+        match event.model_ident {
+            Some(ref model) => quote! {
+                match #model.try_lock() {
+                    Ok(mut model_guard) => {
+                        let #model = &mut *model_guard;
+                        Some(#value)
+                    },
+                    Err(_) => None,
+                }
+            },
+            None => quote! {
+                Some(#value)
+            },
+        };
+    quote! {
+        let reply = #reply;
+        reply.map(|(msg, return_value)| {
+            let msg: Option<_> = msg.into();
+            if let Some(msg) = msg {
+                stream.emit(msg);
+            }
+            return_value
+        })
+    }
+}
+
+fn gen_reply_value(event_value: &EventValueReturn) -> Tokens {
+    match *event_value {
+        Return(ref msg, ref value) => quote! { (#msg, #value) },
+        CallReturn(ref func) => quote! { #func },
+        WithoutReturn(_) => unreachable!("gen_reply_value() is only called for handlers with a return value"),
+    }
+}
+
 fn gen_relm_component_type(name: &Path) -> Path {
     let tokens = quote! {
         ::relm::Component<#name>
@@ -0,0 +1,641 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+/*
+ * Convert a GtkBuilder file, as created by Glade, to the same widget tree as the view! macro.
+ */
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+
+use quote::Tokens;
+use syn::{self, Delimited, TokenTree, parse_path, parse_token_trees};
+use syn::DelimToken::Brace;
+use syn::Token::{Colon, Comma, Ident};
+
+use diagnostics::show_token;
+use parser::{Event, GtkWidget, Widget, parse_widget};
+use parser::EitherWidget::Gtk;
+use parser::EventValue::CurrentWidget;
+use parser::EventValueReturn::WithoutReturn;
+
+// The responses of the dialogs, by their nick.
+const RESPONSES: &[(&str, i32)] = &[("accept", -3), ("apply", -10), ("cancel", -6), ("close", -7),
+    ("delete-event", -4), ("help", -11), ("no", -9), ("none", -1), ("ok", -5), ("reject", -2), ("yes", -8)];
+
+struct Element {
+    attributes: HashMap<String, String>,
+    children: Vec<Element>,
+    name: String,
+    text: String,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    fn required_attribute(&self, name: &str) -> Result<&str, String> {
+        self.attribute(name)
+            .ok_or_else(|| format!("Expected attribute {} in <{}>", name, self.name))
+    }
+}
+
+/*
+ * Parse the glade file, then add the properties and events of the extra tokens, as in:
+ * view!("file.glade", object_id { text: &model.text, clicked => Click })
+ * When the file has several toplevel objects, the root of the view is selected with its id, as in:
+ * view!("file.glade", root: window, object_id { ... })
+ */
+pub fn parse(glade_file: &str, extra_tokens: &[TokenTree]) -> Result<Widget, String> {
+    let mut file_content = String::new();
+    File::open(glade_file)
+        .and_then(|mut file| file.read_to_string(&mut file_content))
+        .map_err(|error| format!("Cannot read glade file {}: {}", glade_file, error))?;
+    let (root, extra_tokens) = parse_root(extra_tokens)?;
+    let mut widget = interface_to_widget(&file_content, root.as_ref())
+        .map_err(|error| format!("{} in glade file {}", error, glade_file))?;
+    add_extras(&mut widget, extra_tokens)?;
+    Ok(widget)
+}
+
+fn parse_root(mut tokens: &[TokenTree]) -> Result<(Option<syn::Ident>, &[TokenTree]), String> {
+    if tokens.first() == Some(&TokenTree::Token(Comma)) {
+        tokens = &tokens[1..];
+    }
+    if tokens.len() >= 3 && tokens[0] == TokenTree::Token(Ident(syn::Ident::new("root")))
+        && tokens[1] == TokenTree::Token(Colon)
+    {
+        match tokens[2] {
+            TokenTree::Token(Ident(ref ident)) => return Ok((Some(ident.clone()), &tokens[3..])),
+            _ => return Err(format!("Expected object id after `root:` but found `{}` in view! macro",
+                show_token(&tokens[2]))),
+        }
+    }
+    Ok((None, tokens))
+}
+
+/*
+ * Convert the toplevel object of the interface, or the one with the id root, to a widget.
+ * The other toplevel objects are ignored.
+ */
+fn interface_to_widget(content: &str, root: Option<&syn::Ident>) -> Result<Widget, String> {
+    let interface = XmlParser::new(content).parse()?;
+    if interface.name != "interface" {
+        return Err(format!("Expected <interface> but found <{}>", interface.name));
+    }
+    let mut objects = vec![];
+    for element in &interface.children {
+        match element.name.as_ref() {
+            "object" => objects.push(element),
+            "requires" => (),
+            name => return Err(format!("Unsupported element <{}>", name)),
+        }
+    }
+    let object =
+        match root {
+            Some(root) => objects.into_iter()
+                .find(|object| object.attribute("id").map(to_ident).as_ref() == Some(root))
+                .ok_or_else(|| format!("No toplevel object with id `{}`", root))?,
+            None if objects.len() == 1 => objects[0],
+            None => return Err(format!("Expected one toplevel object but found {}: select the root with \
+                `root: id` in view! macro", objects.len())),
+        };
+    object_to_widget(object)
+}
+
+fn add_extras(widget: &mut Widget, mut tokens: &[TokenTree]) -> Result<(), String> {
+    while !tokens.is_empty() {
+        if tokens[0] == TokenTree::Token(Comma) {
+            tokens = &tokens[1..];
+            continue;
+        }
+        let id =
+            match tokens[0] {
+                TokenTree::Token(Ident(ref ident)) => ident.clone(),
+                _ => return Err(format!("Expected object id but found `{}` in view! macro", show_token(&tokens[0]))),
+            };
+        let body =
+            match tokens.get(1) {
+                Some(body @ &TokenTree::Delimited(Delimited { delim: Brace, .. })) => body,
+                token => return Err(format!("Expected `{{` after `{}` but found `{}` in view! macro", id,
+                    token.map(show_token).unwrap_or_default())),
+            };
+        let object = find_widget(widget, &id)
+            .ok_or_else(|| format!("No object with id `{}` in glade file", id))?;
+        // Parse the extra properties and events as if they were written on the object itself.
+        let typ = &object.typ;
+        let widget_tokens = quote! {
+            #typ #body
+        };
+        let widget_tokens = parse_token_trees(widget_tokens.as_str())
+            .map_err(|_| format!("Cannot parse the class `{}` of the glade object `{}`", quote! { #typ }, id))?;
+        let (extra, _) = parse_widget(&widget_tokens, false)?;
+        if !extra.children.is_empty() {
+            return Err(format!("Cannot add children to the glade object `{}` in view! macro", id));
+        }
+        if let Gtk(ref mut object_widget) = object.widget {
+            // The properties of the view! macro replace the ones of the glade file.
+            object_widget.glade_properties.retain(|&(ref name, _)| !extra.properties.contains_key(name));
+            object_widget.glade_child_properties.retain(|&(ref name, _)|
+                !extra.child_properties.contains_key(name));
+        }
+        object.properties.extend(extra.properties);
+        object.child_properties.extend(extra.child_properties);
+        if let (&mut Gtk(ref mut object_widget), Gtk(extra_widget)) = (&mut object.widget, extra.widget) {
+            object_widget.accels.extend(extra_widget.accels);
+            object_widget.actions.extend(extra_widget.actions);
+            object_widget.bindings.extend(extra_widget.bindings);
+            object_widget.events.extend(extra_widget.events);
+            object_widget.signals.extend(extra_widget.signals);
+        }
+        tokens = &tokens[2..];
+    }
+    Ok(())
+}
+
+fn class_to_path(class: &str) -> Result<syn::Path, String> {
+    if !class.starts_with("Gtk") {
+        return Err(format!("Unsupported class {}", class));
+    }
+    parse_path(&format!("gtk::{}", &class[3..]))
+        .map_err(|_| format!("Unsupported class {}", class))
+}
+
+fn find_widget<'a>(widget: &'a mut Widget, name: &syn::Ident) -> Option<&'a mut Widget> {
+    if widget.name == *name {
+        return Some(widget);
+    }
+    widget.children.iter_mut()
+        .filter_map(|child| find_widget(child, name))
+        .next()
+}
+
+fn object_to_widget(object: &Element) -> Result<Widget, String> {
+    let class = object.required_attribute("class")?;
+    let typ = class_to_path(class)?;
+    let id = object.attribute("id").map(to_ident);
+    let mut gtk_widget = GtkWidget::new();
+    // Objects with an id are saved, as with #[name].
+    gtk_widget.save = id.is_some();
+    let mut properties = HashMap::new();
+    let mut children = vec![];
+    let mut action_widgets = vec![];
+    for element in &object.children {
+        match element.name.as_ref() {
+            "action-widgets" => {
+                for action_widget in &element.children {
+                    let response = action_widget.required_attribute("response")?;
+                    action_widgets.push((to_ident(&action_widget.text), response_id(response)?));
+                }
+            },
+            "child" => {
+                if let Some(child) = parse_glade_child(element)? {
+                    children.push(child);
+                }
+            },
+            "property" => {
+                let name = property_name(element)?;
+                // Every widget is shown by default, so only a hidden widget needs the property, which is
+                // set after the widget is shown.
+                if name == "visible" {
+                    if !is_true(&element.text) {
+                        properties.insert(name, quote! { false });
+                    }
+                }
+                else {
+                    gtk_widget.glade_properties.push((name, element.text.clone()));
+                }
+            },
+            "signal" => {
+                let name = element.required_attribute("name")?.replace('-', "_");
+                let handler = element.required_attribute("handler")?;
+                let mut value = Tokens::new();
+                value.append(handler);
+                let event = Event {
+                    model_ident: None,
+                    params: vec![syn::Ident::new("_")],
+                    value: CurrentWidget(WithoutReturn(value)),
+                };
+                gtk_widget.events.insert(name, event);
+            },
+            name => return Err(format!("Unsupported element <{}> in <object class=\"{}\">", name, class)),
+        }
+    }
+    let mut widget = Widget::new_gtk(gtk_widget, typ, vec![], children, properties, HashMap::new());
+    if let Some(id) = id {
+        widget.name = id;
+    }
+    for (id, response) in action_widgets {
+        match find_widget(&mut widget, &id) {
+            Some(&mut Widget { widget: Gtk(ref mut action_widget), .. }) => action_widget.response = Some(response),
+            _ => return Err(format!("No action widget with id `{}` in <object class=\"{}\">", id, class)),
+        }
+    }
+    Ok(widget)
+}
+
+fn parse_glade_child(child: &Element) -> Result<Option<Widget>, String> {
+    if child.attribute("type").is_some() {
+        return Err("Unsupported typed <child>".to_string());
+    }
+    let mut widget = None;
+    let mut child_properties = vec![];
+    for element in &child.children {
+        match element.name.as_ref() {
+            "object" => widget = Some(object_to_widget(element)?),
+            "packing" => {
+                for property in &element.children {
+                    child_properties.push((property_name(property)?, property.text.clone()));
+                }
+            },
+            "placeholder" => (),
+            name => return Err(format!("Unsupported element <{}> in <child>", name)),
+        }
+    }
+    Ok(widget.map(|mut widget| {
+        if let Gtk(ref mut gtk_widget) = widget.widget {
+            gtk_widget.glade_child_properties = child_properties;
+            // The internal children, like the action area of a dialog, are created by their parent.
+            gtk_widget.internal_child = child.attribute("internal-child").map(str::to_string);
+        }
+        widget
+    }))
+}
+
+fn is_true(value: &str) -> bool {
+    let value = value.to_lowercase();
+    value == "true" || value == "yes"
+}
+
+fn property_name(property: &Element) -> Result<String, String> {
+    Ok(property.required_attribute("name")?.replace('-', "_"))
+}
+
+/*
+ * Get the response of a dialog from its number or its nick, like -5 or ok.
+ */
+fn response_id(response: &str) -> Result<i32, String> {
+    if let Ok(id) = response.parse() {
+        return Ok(id);
+    }
+    let nick = response.to_lowercase().replace('_', "-");
+    let nick =
+        if nick.starts_with("gtk-response-") {
+            &nick["gtk-response-".len()..]
+        }
+        else {
+            &nick[..]
+        };
+    match RESPONSES.iter().find(|&&(name, _)| name == nick) {
+        Some(&(_, id)) => Ok(id),
+        None => Err(format!("Unknown response {}", response)),
+    }
+}
+
+fn to_ident(id: &str) -> syn::Ident {
+    syn::Ident::new(id.replace('-', "_"))
+}
+
+/*
+ * Minimal XML parser, supporting what is needed for GtkBuilder files.
+ */
+struct XmlParser<'a> {
+    input: &'a str,
+}
+
+impl<'a> XmlParser<'a> {
+    fn new(input: &'a str) -> Self {
+        XmlParser {
+            input: input,
+        }
+    }
+
+    fn parse(&mut self) -> Result<Element, String> {
+        self.skip_misc()?;
+        let element = self.parse_element()?;
+        self.skip_misc()?;
+        if !self.input.is_empty() {
+            return Err(format!("Unexpected content after the root element near `{}`", self.context()));
+        }
+        Ok(element)
+    }
+
+    fn expect(&mut self, string: &str) -> Result<(), String> {
+        if !self.input.starts_with(string) {
+            return Err(format!("Expected `{}` near `{}`", string, self.context()));
+        }
+        self.input = &self.input[string.len()..];
+        Ok(())
+    }
+
+    fn context(&self) -> &str {
+        let end = self.input.char_indices().nth(30).map(|(index, _)| index).unwrap_or(self.input.len());
+        &self.input[..end]
+    }
+
+    /*
+     * Skip the input until after the end string, returning the skipped text.
+     */
+    fn skip_past(&mut self, end: &str, what: &str) -> Result<&'a str, String> {
+        let index = self.input.find(end)
+            .ok_or_else(|| format!("Expected the end of the {} near `{}`", what, self.context()))?;
+        let skipped = &self.input[..index];
+        self.input = &self.input[index + end.len()..];
+        Ok(skipped)
+    }
+
+    fn parse_element(&mut self) -> Result<Element, String> {
+        self.expect("<")?;
+        let name = self.parse_name()?;
+        let mut attributes = HashMap::new();
+        loop {
+            self.skip_whitespace();
+            if self.input.starts_with("/>") {
+                self.input = &self.input[2..];
+                return Ok(Element {
+                    attributes: attributes,
+                    children: vec![],
+                    name: name,
+                    text: String::new(),
+                });
+            }
+            if self.input.starts_with('>') {
+                self.input = &self.input[1..];
+                break;
+            }
+            let attribute = self.parse_name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote =
+                match self.input.chars().next() {
+                    Some('"') => "\"",
+                    Some('\'') => "'",
+                    _ => return Err(format!("Expected quoted attribute value near `{}`", self.context())),
+                };
+            self.input = &self.input[1..];
+            let value = self.skip_past(quote, "attribute value")?;
+            attributes.insert(attribute, decode_entities(value)?);
+        }
+        let mut children = vec![];
+        let mut text = String::new();
+        loop {
+            if self.input.starts_with("</") {
+                self.input = &self.input[2..];
+                let end_name = self.parse_name()?;
+                if end_name != name {
+                    return Err(format!("Expected </{}> but found </{}>", name, end_name));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                break;
+            }
+            else if self.input.starts_with("<!--") {
+                self.skip_past("-->", "comment")?;
+            }
+            else if self.input.starts_with("<![CDATA[") {
+                self.input = &self.input[9..];
+                text.push_str(self.skip_past("]]>", "CDATA")?);
+            }
+            else if self.input.starts_with('<') {
+                children.push(self.parse_element()?);
+            }
+            else if self.input.is_empty() {
+                return Err(format!("Expected </{}>", name));
+            }
+            else {
+                let end = self.input.find('<').unwrap_or(self.input.len());
+                text.push_str(&decode_entities(&self.input[..end])?);
+                self.input = &self.input[end..];
+            }
+        }
+        if !children.is_empty() {
+            text = text.trim().to_string();
+        }
+        Ok(Element {
+            attributes: attributes,
+            children: children,
+            name: name,
+            text: text,
+        })
+    }
+
+    fn parse_name(&mut self) -> Result<String, String> {
+        let end = self.input.find(|chr: char| chr.is_whitespace() || chr == '=' || chr == '/' || chr == '>')
+            .unwrap_or(self.input.len());
+        if end == 0 {
+            return Err(format!("Expected name near `{}`", self.context()));
+        }
+        let name = self.input[..end].to_string();
+        self.input = &self.input[end..];
+        Ok(name)
+    }
+
+    /*
+     * Skip the whitespaces, the comments, the XML declaration and the doctype.
+     */
+    fn skip_misc(&mut self) -> Result<(), String> {
+        loop {
+            self.skip_whitespace();
+            if self.input.starts_with("<!--") {
+                self.skip_past("-->", "comment")?;
+            }
+            else if self.input.starts_with("<?") || self.input.starts_with("<!") {
+                self.skip_past(">", "declaration")?;
+            }
+            else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let start = self.input.find(|chr: char| !chr.is_whitespace()).unwrap_or(self.input.len());
+        self.input = &self.input[start..];
+    }
+}
+
+fn decode_entities(text: &str) -> Result<String, String> {
+    let mut result = String::new();
+    let mut text = text;
+    while let Some(start) = text.find('&') {
+        result.push_str(&text[..start]);
+        text = &text[start..];
+        let end = text.find(';').ok_or_else(|| format!("Expected `;` after the entity `{}`", text))?;
+        let entity = &text[1..end];
+        let chr =
+            match entity {
+                "amp" => Some('&'),
+                "apos" => Some('\''),
+                "gt" => Some('>'),
+                "lt" => Some('<'),
+                "quot" => Some('"'),
+                _ if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16).ok()
+                    .and_then(::std::char::from_u32),
+                _ if entity.starts_with('#') => entity[1..].parse().ok()
+                    .and_then(::std::char::from_u32),
+                _ => None,
+            };
+        result.push(chr.ok_or_else(|| format!("Unknown entity &{};", entity))?);
+        text = &text[end + 1..];
+    }
+    result.push_str(text);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::Ident;
+
+    use parser::EitherWidget::Gtk;
+    use parser::{GtkWidget, Widget};
+    use super::{XmlParser, decode_entities, interface_to_widget, response_id};
+
+    const DIALOG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- Generated with glade 3.20.0 -->
+<interface>
+  <requires lib="gtk+" version="3.12"/>
+  <object class="GtkAdjustment" id="adjustment1">
+    <property name="upper">100</property>
+  </object>
+  <object class="GtkDialog" id="dialog">
+    <property name="title" translatable="yes">Save &amp; quit</property>
+    <property name="visible">False</property>
+    <child internal-child="vbox">
+      <object class="GtkBox" id="dialog-vbox">
+        <child internal-child="action_area">
+          <object class="GtkButtonBox" id="dialog-action_area">
+            <child>
+              <object class="GtkButton" id="ok_button">
+                <property name="label">gtk-ok</property>
+              </object>
+              <packing>
+                <property name="expand">False</property>
+                <property name="position">0</property>
+              </packing>
+            </child>
+          </object>
+        </child>
+        <child>
+          <placeholder/>
+        </child>
+      </object>
+    </child>
+    <action-widgets>
+      <action-widget response="ok">ok_button</action-widget>
+    </action-widgets>
+  </object>
+</interface>"#;
+
+    fn gtk_widget(widget: &Widget) -> &GtkWidget {
+        match widget.widget {
+            Gtk(ref gtk_widget) => gtk_widget,
+            _ => panic!("Expected a gtk widget"),
+        }
+    }
+
+    fn properties(widget: &Widget) -> Vec<(&str, &str)> {
+        gtk_widget(widget).glade_properties.iter()
+            .map(|&(ref name, ref value)| (name.as_str(), value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn elements() {
+        let element = XmlParser::new(r#"<?xml version="1.0"?>
+<!DOCTYPE interface>
+<!-- A comment -->
+<interface a="1" b = 'two'>
+  <empty/>
+  <text>Some <!-- comment --> text</text>
+</interface>
+"#).parse().expect("element");
+        assert_eq!(element.name, "interface");
+        assert_eq!(element.attribute("a"), Some("1"));
+        assert_eq!(element.attribute("b"), Some("two"));
+        assert_eq!(element.children.len(), 2);
+        assert_eq!(element.children[0].name, "empty");
+        assert!(element.children[0].children.is_empty());
+        assert_eq!(element.children[1].text, "Some  text");
+        // The whitespaces between the children are not kept.
+        assert_eq!(element.text, "");
+    }
+
+    #[test]
+    fn entities_and_cdata() {
+        let source = r#"<a title="&quot;x&quot; &lt; y">&lt;b&gt; &amp; <![CDATA[<c> &amp;]]>&#65;&#x42;</a>"#;
+        let element = XmlParser::new(source).parse().expect("element");
+        assert_eq!(element.attribute("title"), Some("\"x\" < y"));
+        assert_eq!(element.text, "<b> & <c> &amp;AB");
+        assert_eq!(decode_entities("&apos;&#x263A;&#9731;"), Ok("'☺☃".to_string()));
+        assert_eq!(decode_entities("&nbsp;"), Err("Unknown entity &nbsp;".to_string()));
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let error = XmlParser::new("<b><c/></a>").parse().err().expect("mismatched end tag");
+        assert_eq!(error, "Expected </b> but found </a>");
+        let error = XmlParser::new("<a title=\"x></a>").parse().err().expect("unterminated attribute");
+        assert_eq!(error, "Expected the end of the attribute value near `x></a>`");
+        let error = XmlParser::new("<a><!-- x</a>").parse().err().expect("unterminated comment");
+        assert_eq!(error, "Expected the end of the comment near `<!-- x</a>`");
+    }
+
+    #[test]
+    fn root_selected_by_id() {
+        let error = interface_to_widget(DIALOG, None).err().expect("several toplevel objects");
+        assert!(error.starts_with("Expected one toplevel object but found 2"));
+        let error = interface_to_widget(DIALOG, Some(&Ident::new("window"))).err().expect("unknown root");
+        assert_eq!(error, "No toplevel object with id `window`");
+        let dialog = interface_to_widget(DIALOG, Some(&Ident::new("dialog"))).expect("dialog");
+        assert_eq!(dialog.name, Ident::new("dialog"));
+        // The properties are converted at runtime, except visible which is set after the widget is shown.
+        assert_eq!(properties(&dialog), vec![("title", "Save & quit")]);
+        assert_eq!(dialog.properties["visible"].as_str(), "false");
+    }
+
+    #[test]
+    fn internal_children_and_action_widgets() {
+        let dialog = interface_to_widget(DIALOG, Some(&Ident::new("dialog"))).expect("dialog");
+        assert_eq!(dialog.children.len(), 1);
+        let vbox = &dialog.children[0];
+        assert_eq!(vbox.name, Ident::new("dialog_vbox"));
+        assert_eq!(gtk_widget(vbox).internal_child, Some("vbox".to_string()));
+        let action_area = &vbox.children[0];
+        assert_eq!(gtk_widget(action_area).internal_child, Some("action_area".to_string()));
+        let button = &action_area.children[0];
+        assert_eq!(gtk_widget(button).internal_child, None);
+        assert_eq!(gtk_widget(button).response, Some(-5));
+        assert_eq!(gtk_widget(button).glade_child_properties,
+            vec![("expand".to_string(), "False".to_string()), ("position".to_string(), "0".to_string())]);
+    }
+
+    #[test]
+    fn responses() {
+        assert_eq!(response_id("-6"), Ok(-6));
+        assert_eq!(response_id("cancel"), Ok(-6));
+        assert_eq!(response_id("GTK_RESPONSE_DELETE_EVENT"), Ok(-4));
+        assert_eq!(response_id("3"), Ok(3));
+        assert_eq!(response_id("maybe"), Err("Unknown response maybe".to_string()));
+    }
+}
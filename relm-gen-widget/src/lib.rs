@@ -21,8 +21,6 @@
 
 /*
  * TODO: automatically add the model() method with a () return type when it is not found?
- * FIXME: Doing model.text.push_str() will not cause a set_text() to be added.
- * TODO: think about conditions and loops (widget-list).
  */
 
 #[macro_use]
@@ -32,20 +30,25 @@ extern crate quote;
 extern crate syn;
 
 mod adder;
+mod diagnostics;
 mod gen;
+mod glade;
 mod parser;
 mod walker;
 
 use std::collections::{HashMap, HashSet};
 
-use adder::{Adder, Property};
+use adder::{Adder, Parent, Property, last_value_ident};
+use diagnostics::{catch_errors, did_you_mean, show_token};
 use gen::gen;
 use parser::EitherWidget::{Gtk, Relm};
-use parser::{Widget, parse};
+use parser::{Prop, Widget, add_fragments, clear_fragments, parse, parse_props};
 use quote::Tokens;
 use syn::{
     AngleBracketedParameterData,
+    Attribute,
     Delimited,
+    Expr,
     FunctionRetTy,
     Generics,
     Ident,
@@ -57,27 +60,42 @@ use syn::{
     TokenTree,
     parse_expr,
     parse_item,
+    parse_token_trees,
 };
 use syn::FnArg::Captured;
-use syn::fold::Folder;
 use syn::ImplItemKind::{Const, Macro, Method, Type};
+use syn::ExprKind::Tup as TupExpr;
+use syn::DelimToken::Paren;
 use syn::ItemKind::Impl;
 use syn::Pat::Wild;
 use syn::PathParameters::AngleBracketed;
+use syn::Token::{DocComment, Pound};
+use syn::TokenTree::Token;
 use syn::Ty::{self, Tup};
 use syn::visit::Visitor;
-use walker::ModelVariableVisitor;
+use walker::{ModelPath, ModelVariableVisitor};
 
-type PropertyModelMap = HashMap<Ident, HashSet<Property>>;
+type PropertyModelMap = HashMap<ModelPath, HashSet<Property>>;
+
+// The methods and types which can be written in the impl of a widget.
+const METHODS: &[&str] = &["init_view", "model", "parent_id", "placement", "root", "subscriptions", "update",
+    "update_command"];
+const TYPES: &[&str] = &["Model", "ModelParam", "Msg", "Root"];
 
 #[derive(Debug)]
 pub struct Driver {
+    bound_fields: Vec<Tokens>,
+    dynamic_fields: Vec<(Ident, Tokens)>,
+    dynamic_methods: Vec<Tokens>,
     data_method: Option<ImplItem>,
     generic_types: Option<Generics>,
+    // The fields saving the last values given to the setters in update().
+    last_values: Vec<Ident>,
     model_type: Option<ImplItem>,
     model_param_type: Option<ImplItem>,
     msg_type: Option<ImplItem>,
     properties_model_map: Option<PropertyModelMap>,
+    props: Vec<Prop>,
     root_method: Option<ImplItem>,
     root_type: Option<ImplItem>,
     root_widget: Option<Ident>,
@@ -102,14 +120,19 @@ struct View {
 impl Driver {
     fn new() -> Self {
         Driver {
+            bound_fields: vec![],
+            dynamic_fields: vec![],
+            dynamic_methods: vec![],
             data_method: None,
             generic_types: None,
+            last_values: vec![],
             root_method: None,
             root_type: None,
             model_type: None,
             model_param_type: None,
             msg_type: None,
             properties_model_map: None,
+            props: vec![],
             root_widget: None,
             root_widget_expr: None,
             root_widget_type: None,
@@ -124,70 +147,128 @@ impl Driver {
 
     fn add_widgets(&mut self, widget: &Widget, map: &PropertyModelMap) {
         // Only add widgets that are needed by the update() function.
-        let mut to_add = false;
-        for values in map.values() {
-            for value in values {
-                if value.widget_name == widget.name {
-                    to_add = true;
-                }
-            }
-        }
-        if to_add {
+        // The placeholder of a condition or a list is always needed by the method updating its widgets.
+        if is_in_map(&widget.name, map) || widget.condition.is_some() || widget.list.is_some() {
             let widget_type = &widget.typ;
             let typ = quote! {
                 #widget_type
             };
             self.widgets.insert(widget.name.clone(), typ);
         }
+        if let Gtk(ref gtk_widget) = widget.widget {
+            for action in &gtk_widget.actions {
+                if is_in_map(&action.name, map) {
+                    self.widgets.insert(action.name.clone(), quote! {
+                        ::relm::Action
+                    });
+                }
+            }
+        }
         for child in &widget.children {
             self.add_widgets(child, map);
         }
     }
 
-    fn create_struct(&self, typ: &Ty, relm_widgets: &HashMap<Ident, Path>) -> Tokens {
+    /*
+     * The setter of a property declared with props! sends the message to the widget, so that its
+     * model is updated by update().
+     */
+    fn add_prop_setters(&mut self) {
+        for prop in &self.props {
+            let message = &prop.message;
+            let setter = Ident::new(format!("set_{}", prop.name));
+            let typ = &prop.typ;
+            self.dynamic_methods.push(quote! {
+                pub fn #setter(&self, value: #typ) {
+                    self.__relm_remote.stream().emit(#message(value.into()));
+                }
+            });
+        }
+    }
+
+    /*
+     * The struct gets the generics of the impl, with their where clause, and the visibility and the doc
+     * comments written on the impl.
+     */
+    fn create_struct(&self, typ: &Ty, generics: &Generics, attributes: &[Attribute], visibility: &Tokens,
+        relm_widgets: &HashMap<Ident, Path>) -> Result<Tokens, String>
+    {
         let widgets = self.widgets.iter().filter(|&(ident, _)| !relm_widgets.contains_key(ident));
         let (idents, types): (Vec<_>, Vec<_>) = widgets.unzip();
         let relm_idents = relm_widgets.keys();
         let relm_types = relm_widgets.values();
+        let (dynamic_idents, dynamic_types): (Vec<_>, Vec<_>) = self.dynamic_fields.iter()
+            .map(|&(ref ident, ref typ)| (ident, typ))
+            .unzip();
         let phantom_field = get_phantom_field(typ);
-        quote! {
+        let name = get_struct_name(typ)?;
+        let where_clause = &generics.where_clause;
+        let visibility =
+            if visibility.as_str().is_empty() {
+                quote! { pub }
+            }
+            else {
+                visibility.clone()
+            };
+        Ok(quote! {
+            #(#attributes)*
             #[allow(dead_code)]
             #[derive(ManualClone)]
-            pub struct #typ {
+            #visibility struct #name #generics #where_clause {
                 #(#idents: #types,)*
                 #(#relm_idents: #relm_types,)*
+                #(#dynamic_idents: #dynamic_types,)*
                 #phantom_field
             }
-        }
+        })
     }
 
-    fn gen_widget(&mut self, input: Tokens) -> Tokens {
-        let source = input.to_string();
+    fn gen_widget(&mut self, input: Tokens) -> Result<Tokens, String> {
+        let (source, visibility) = split_visibility(&input.to_string());
         let mut ast = parse_item(&source).expect("parse_item() in gen_widget()");
+        clear_fragments();
+        // The doc comments are for the widget struct.
+        let (doc_attributes, attributes): (Vec<Attribute>, _) = ast.attrs.into_iter()
+            .partition(|attribute| attribute.name() == "doc");
+        ast.attrs = attributes;
         if let Impl(unsafety, polarity, generics, path, typ, items) = ast.node {
             self.generic_types = Some(generics.clone());
-            let name = get_name(&typ);
+            let name = get_name(&typ)?;
             let mut new_items = vec![];
             for item in items {
                 let mut i = item.clone();
                 match item.node {
-                    Const(_, _) => panic!("Unexpected const item"),
-                    Macro(mac) => self.view_macro = Some(mac),
+                    Const(_, _) => return Err(format!("Unexpected const item {} in widget impl", item.ident)),
+                    Macro(mac) => {
+                        let name =
+                            if mac.path.segments.len() == 1 {
+                                mac.path.segments[0].ident.to_string()
+                            }
+                            else {
+                                String::new()
+                            };
+                        match name.as_ref() {
+                            "fragment" => add_fragments(&mac.tts)?,
+                            "props" => self.props.extend(parse_props(&mac.tts)?),
+                            _ => self.view_macro = Some(mac),
+                        }
+                    },
                     Method(sig, _) => {
                         match item.ident.to_string().as_ref() {
                             "parent_id" => self.data_method = Some(i),
                             "root" => self.root_method = Some(i),
                             "model" => {
-                                self.widget_model_type = Some(get_return_type(sig));
+                                self.widget_model_type = Some(get_return_type(sig)?);
                                 add_model_param(&mut i, &mut self.model_param_type);
                                 new_items.push(i);
                             },
-                            "init_view" | "subscriptions" | "update_command" => new_items.push(i),
+                            "init_view" | "placement" | "subscriptions" | "update_command" => new_items.push(i),
                             "update" => {
-                                self.widget_msg_type = Some(get_second_param_type(&sig));
+                                self.widget_msg_type = Some(get_second_param_type(&sig)?);
                                 self.update_method = Some(i)
                             },
-                            method_name => panic!("Unexpected method {}", method_name),
+                            method_name => return Err(format!("Unexpected method {} in widget impl{}", method_name,
+                                did_you_mean(method_name, METHODS))),
                         }
                     },
                     Type(_) => {
@@ -196,12 +277,13 @@ impl Driver {
                             "Model" => self.model_type = Some(i),
                             "ModelParam" => self.model_param_type = Some(i),
                             "Msg" => self.msg_type = Some(i),
-                            _ => panic!("Unexpected type item {:?}", item.ident),
+                            _ => return Err(format!("Unexpected type item {} in widget impl{}", item.ident,
+                                did_you_mean(item.ident.as_ref(), TYPES))),
                         }
                     },
                 }
             }
-            let view = self.get_view(&name, &typ);
+            let view = self.get_view(&name, &typ)?;
             if let Some(on_add) = gen_set_child_prop_calls(&view.widget) {
                 new_items.push(on_add);
             }
@@ -209,27 +291,43 @@ impl Driver {
             new_items.push(view.item);
             self.widgets.insert(self.root_widget.clone().expect("root widget"),
             self.root_widget_type.clone().expect("root widget type"));
-            new_items.push(self.get_msg_type());
-            new_items.push(self.get_model_type());
+            new_items.push(self.get_msg_type()?);
+            new_items.push(self.get_model_type()?);
             new_items.push(self.get_model_param_type());
             new_items.push(self.get_root_type());
             if let Some(data_method) = self.get_data_method() {
                 new_items.push(data_method);
             }
-            new_items.push(self.get_update());
+            new_items.push(self.get_update()?);
             new_items.push(self.get_root());
-            let widget_struct = self.create_struct(&typ, &view.relm_widgets);
+            self.add_prop_setters();
+            let widget_struct = self.create_struct(&typ, &generics, &doc_attributes, &visibility, &view.relm_widgets)?;
+            let dynamic_impl =
+                if self.dynamic_methods.is_empty() {
+                    quote! {
+                    }
+                }
+                else {
+                    let methods = &self.dynamic_methods;
+                    let where_clause = &generics.where_clause;
+                    quote! {
+                        impl #generics #typ #where_clause {
+                            #(#methods)*
+                        }
+                    }
+                };
             let item = Impl(unsafety, polarity, generics, path, typ, new_items);
             ast.node = item;
             let container_impl = view.container_impl;
-            quote! {
+            Ok(quote! {
                 #widget_struct
                 #ast
                 #container_impl
-            }
+                #dynamic_impl
+            })
         }
         else {
-            panic!("Expected impl");
+            Err("Expected an impl of the Widget trait".to_string())
         }
     }
 
@@ -256,22 +354,26 @@ impl Driver {
         })
     }
 
-    fn get_model_type(&mut self) -> ImplItem {
-        self.model_type.take().unwrap_or_else(|| {
-            let widget_model_type = self.widget_model_type.take().expect("missing model method");
-            block_to_impl_item(quote! {
-                type Model = #widget_model_type;
-            })
-        })
+    fn get_model_type(&mut self) -> Result<ImplItem, String> {
+        if let Some(model_type) = self.model_type.take() {
+            return Ok(model_type);
+        }
+        let widget_model_type = self.widget_model_type.take()
+            .ok_or_else(|| "Missing model() method in widget impl".to_string())?;
+        Ok(block_to_impl_item(quote! {
+            type Model = #widget_model_type;
+        }))
     }
 
-    fn get_msg_type(&mut self) -> ImplItem {
-        self.msg_type.take().unwrap_or_else(|| {
-            let widget_msg_type = self.widget_msg_type.take().expect("missing update method");
-            block_to_impl_item(quote! {
-                type Msg = #widget_msg_type;
-            })
-        })
+    fn get_msg_type(&mut self) -> Result<ImplItem, String> {
+        if let Some(msg_type) = self.msg_type.take() {
+            return Ok(msg_type);
+        }
+        let widget_msg_type = self.widget_msg_type.take()
+            .ok_or_else(|| "Missing update() method in widget impl".to_string())?;
+        Ok(block_to_impl_item(quote! {
+            type Msg = #widget_msg_type;
+        }))
     }
 
     fn get_root(&mut self) -> ImplItem {
@@ -294,40 +396,46 @@ impl Driver {
         })
     }
 
-    /*
-     * TODO: Create a control flow graph for each variable of the model.
-     * Add the set_property() calls in every leaf of every graphs.
-     */
-    fn get_update(&mut self) -> ImplItem {
-        let mut func = self.update_method.take().expect("update method");
+    fn get_update(&mut self) -> Result<ImplItem, String> {
+        let mut func = self.update_method.take()
+            .ok_or_else(|| "Missing update() method in widget impl".to_string())?;
         if let Method(_, ref mut block) = func.node {
-            let mut adder = Adder::new(self.properties_model_map.as_ref().expect("update method"));
-            *block = adder.fold_block(block.clone());
+            let mut adder = Adder::new(self.properties_model_map.as_ref().expect("update method"), &self.bound_fields,
+                &self.last_values);
+            *block = adder.fold_update(block.clone());
         }
         // TODO: consider gtk::main_quit() as return.
-        func
+        Ok(func)
     }
 
-    fn get_view(&mut self, name: &Ident, typ: &Ty) -> View {
+    fn get_view(&mut self, name: &Ident, typ: &Ty) -> Result<View, String> {
         {
-            let segments = &self.view_macro.as_ref().expect("view! macro missing").path.segments;
-            if segments.len() != 1 || segments[0].ident != "view" {
-                panic!("Unexpected macro item")
+            let path = &self.view_macro.as_ref().ok_or_else(|| "Missing view! macro in widget impl".to_string())?
+                .path;
+            if path.segments.len() != 1 || path.segments[0].ident != "view" {
+                return Err(format!("Unexpected macro {}! in widget impl: expected view!, fragment! or props!",
+                    quote! { #path }));
             }
         }
         self.impl_view(name, typ)
     }
 
-    fn impl_view(&mut self, name: &Ident, typ: &Ty) -> View {
+    fn impl_view(&mut self, name: &Ident, typ: &Ty) -> Result<View, String> {
         let tokens = &self.view_macro.take().expect("view_macro in impl_view()").tts;
-        if let TokenTree::Delimited(Delimited { ref tts, .. }) = tokens[0] {
-            let mut widget = parse(tts);
+        if let Some(&TokenTree::Delimited(Delimited { ref tts, .. })) = tokens.first() {
+            let mut widget = parse(tts)?;
             if let Gtk(ref mut widget) = widget.widget {
                 widget.relm_name = Some(typ.clone());
             }
             self.widget_parent_id = widget.parent_id.clone();
             let mut properties_model_map = HashMap::new();
-            get_properties_model_map(&widget, &mut properties_model_map);
+            get_properties_model_map(&widget, &mut properties_model_map, false, None);
+            let mut last_values: Vec<_> = properties_model_map.values()
+                .flat_map(|properties| properties.iter().filter_map(last_value_ident))
+                .collect();
+            last_values.sort_by(|ident1, ident2| ident1.as_ref().cmp(ident2.as_ref()));
+            last_values.dedup();
+            self.last_values = last_values;
             self.add_widgets(&widget, &properties_model_map);
             let (view, relm_widgets, container_impl) = gen(name, typ, &widget, self);
             let item = block_to_impl_item(quote! {
@@ -336,23 +444,101 @@ impl Driver {
                     #view
                 }
             });
-            View {
+            Ok(View {
                 container_impl: container_impl,
                 item: item,
                 properties_model_map: properties_model_map,
                 relm_widgets: relm_widgets,
                 widget: widget,
-            }
+            })
         }
         else {
-            panic!("Expected `{{` but found `{:?}` in view! macro", tokens[0]);
+            Err(format!("Expected `{{` but found `{}` in view! macro",
+                tokens.first().map(show_token).unwrap_or_default()))
         }
     }
 }
 
 pub fn gen_widget(input: Tokens) -> Tokens {
-    let mut driver = Driver::new();
-    driver.gen_widget(input)
+    catch_errors(|| {
+        let mut driver = Driver::new();
+        driver.gen_widget(input)
+    })
+}
+
+/*
+ * Generate the widget like gen_widget() and describe the setters called when a model field is
+ * updated, like `model.counter => gtklabel1.set_text(&model.counter.to_string())`.
+ * This is used by relm-expand.
+ */
+pub fn expand_widget(input: Tokens) -> (Tokens, Vec<String>) {
+    let mut bindings = vec![];
+    let tokens = catch_errors(|| {
+        let mut driver = Driver::new();
+        let tokens = driver.gen_widget(input)?;
+        if let Some(ref map) = driver.properties_model_map {
+            bindings = describe_model_map(map);
+        }
+        Ok(tokens)
+    });
+    (tokens, bindings)
+}
+
+/*
+ * Check whether an expression reads a model field, or None when it is not an expression.
+ * This is used by relm-fmt.
+ */
+pub fn reads_model(expr: &str) -> Option<bool> {
+    parse_expr(expr).ok().map(|expr| {
+        let mut visitor = ModelVariableVisitor::new();
+        visitor.visit_expr(&expr);
+        !visitor.paths.is_empty()
+    })
+}
+
+/*
+ * Check whether an expression reads a variable, like `title` but not `Vertical`, or None when it is
+ * not an expression.
+ * This is used by relm-fmt.
+ */
+pub fn reads_variable(expr: &str) -> Option<bool> {
+    parse_expr(expr).ok().map(|expr| walker::reads_variable(&expr))
+}
+
+fn describe_model_map(map: &PropertyModelMap) -> Vec<String> {
+    let mut bindings = vec![];
+    for (path, properties) in map {
+        let mut model_path = "model".to_string();
+        for field in path {
+            model_path.push('.');
+            model_path.push_str(field.as_ref());
+        }
+        for property in properties {
+            bindings.push(format!("{} => {}", model_path, describe_property(property)));
+        }
+    }
+    bindings.sort();
+    bindings
+}
+
+fn describe_property(property: &Property) -> String {
+    let widget = &property.widget_name;
+    let setter =
+        if property.is_method {
+            format!("self.{}()", property.name)
+        }
+        else if let Some(ref parent) = property.parent {
+            format!("{}: child property {} of {} = {}", parent.name, property.name, widget, property.expr)
+        }
+        else {
+            format!("{}.set_{}({})", widget, property.name, property.expr)
+        };
+    if property.is_optional {
+        format!("{} (when the widget exists)", setter)
+    }
+    else {
+        setter
+    }
 }
 
 fn add_model_param(model_fn: &mut ImplItem, model_param_type: &mut Option<ImplItem>) {
@@ -383,19 +569,32 @@ fn block_to_impl_item(tokens: Tokens) -> ImplItem {
     }
 }
 
-fn get_name(typ: &Ty) -> Ident {
+fn get_struct_name(typ: &Ty) -> Result<&Ident, String> {
+    if let Ty::Path(_, ref path) = *typ {
+        Ok(&path.segments.last().expect("path should have at least one segment").ident)
+    }
+    else {
+        Err(expected_widget_name(typ))
+    }
+}
+
+fn get_name(typ: &Ty) -> Result<Ident, String> {
     if let Ty::Path(_, ref path) = *typ {
         let mut parts = vec![];
         for segment in &path.segments {
             parts.push(segment.ident.as_ref());
         }
-        Ident::new(parts.join("::"))
+        Ok(Ident::new(parts.join("::")))
     }
     else {
-        panic!("Expected Path")
+        Err(expected_widget_name(typ))
     }
 }
 
+fn expected_widget_name(typ: &Ty) -> String {
+    format!("Expected the name of the widget struct after `for` but found `{}`", quote! { #typ })
+}
+
 fn get_generic_types(typ: &Ty) -> Option<Vec<Ident>> {
     if let Ty::Path(_, ref path) = *typ {
         let last_segment = path.segments.last().expect("path should have at least one segment");
@@ -439,72 +638,203 @@ fn get_phantom_field(typ: &Ty) -> Tokens {
     }
 }
 
+/*
+ * Remove the visibility written before impl, as in `pub(crate) impl Widget for Win`, since syn does
+ * not parse it: it is the visibility of the widget struct.
+ */
+fn split_visibility(source: &str) -> (String, Tokens) {
+    let tts = parse_token_trees(source).expect("parse_token_trees() in split_visibility()");
+    let mut index = 0;
+    // Skip the attributes.
+    loop {
+        match tts.get(index) {
+            Some(&Token(DocComment(_))) => index += 1,
+            Some(&Token(Pound)) => index += 2,
+            _ => break,
+        }
+    }
+    let mut visibility = Tokens::new();
+    let mut rest = Tokens::new();
+    if tts.get(index) == Some(&Token(syn::Token::Ident(Ident::new("pub")))) {
+        let mut end = index + 1;
+        if let Some(&TokenTree::Delimited(Delimited { delim: Paren, .. })) = tts.get(end) {
+            end += 1;
+        }
+        visibility.append_all(&tts[index..end]);
+        rest.append_all(&tts[..index]);
+        rest.append_all(&tts[end..]);
+    }
+    else {
+        rest.append_all(&tts);
+    }
+    (rest.to_string(), visibility)
+}
+
+fn is_in_map(name: &Ident, map: &PropertyModelMap) -> bool {
+    map.values().any(|values| values.iter().any(|value|
+        value.widget_name == *name || value.parent.as_ref().map(|parent| &parent.name) == Some(name)))
+}
+
 macro_rules! get_map {
-    ($widget:expr, $map:expr, $is_relm:expr) => {{
-        for (name, value) in &$widget.properties {
+    ($widget:expr, $map:expr, $is_relm:expr, $is_optional:expr) => {
+        get_map!($widget, $widget.properties, "", None, $map, $is_relm, $is_optional)
+    };
+    ($widget:expr, $properties:expr, $prefix:expr, $parent:expr, $map:expr, $is_relm:expr, $is_optional:expr) => {{
+        for (name, value) in &$properties {
             let string: String = value.parse().expect("parse::<String>() in get_map!");
             let expr = parse_expr(&string).expect("parse_expr in get_map!");
             let mut visitor = ModelVariableVisitor::new();
             visitor.visit_expr(&expr);
-            let model_variables = visitor.idents;
-            for var in model_variables {
-                let set = $map.entry(var).or_insert_with(HashSet::new);
+            for path in visitor.paths {
+                let set = $map.entry(path).or_insert_with(HashSet::new);
                 set.insert(Property {
                     expr: string.clone(),
+                    is_method: false,
+                    is_optional: $is_optional,
                     is_relm_widget: $is_relm,
-                    name: name.clone(),
+                    name: format!("{}{}", $prefix, name),
+                    parent: $parent.clone(),
                     widget_name: $widget.name.clone(),
                 });
             }
         }
-        for child in &$widget.children {
-            get_properties_model_map(child, $map);
-        }
     }};
 }
 
 /*
- * The map maps model variable name to a vector of tuples (widget name, property name).
+ * The map maps model field paths to the properties using them.
  */
-fn get_properties_model_map(widget: &Widget, map: &mut PropertyModelMap) {
-    match widget.widget {
-        Gtk(_) => get_map!(widget, map, false),
-        Relm(_) => get_map!(widget, map, true),
+fn get_properties_model_map(widget: &Widget, map: &mut PropertyModelMap, is_optional: bool, parent: Option<&Parent>) {
+    let is_relm =
+        match widget.widget {
+            Gtk(ref gtk_widget) => {
+                for action in &gtk_widget.actions {
+                    get_map!(action, map, false, is_optional);
+                }
+                false
+            },
+            Relm(_) => true,
+        };
+    get_map!(widget, map, is_relm, is_optional);
+    // The child properties of the root widget are set by on_add(), since its parent is not known.
+    if let Some(parent) = parent {
+        get_map!(widget, widget.child_properties, "child_", Some(parent.clone()), map, is_relm, is_optional);
+    }
+    let parent = Parent {
+        is_optional,
+        is_relm_widget: is_relm,
+        name: widget.name.clone(),
+    };
+    if let Some(ref condition) = widget.condition {
+        let method = gen_update_method_name(&widget.name);
+        for &(ref expr, ref branch) in &condition.branches {
+            if let Some(ref expr) = *expr {
+                add_method_to_map(expr, method.as_ref(), &widget.name, map);
+            }
+            get_properties_model_map(branch, map, true, Some(&parent));
+        }
+    }
+    if let Some(ref list) = widget.list {
+        let method = gen_update_method_name(&widget.name);
+        add_method_to_map(&list.items, method.as_ref(), &widget.name, map);
+    }
+    for child in &widget.children {
+        get_properties_model_map(child, map, is_optional, Some(&parent));
+    }
+}
+
+/*
+ * Call the method updating the widgets of a condition or a list when the model variables of expr change.
+ */
+fn add_method_to_map(expr: &Tokens, method: &str, widget_name: &Ident, map: &mut PropertyModelMap) {
+    let string: String = expr.parse().expect("parse::<String>() in add_method_to_map");
+    let expr = parse_expr(&string).expect("parse_expr in add_method_to_map");
+    let mut visitor = ModelVariableVisitor::new();
+    visitor.visit_expr(&expr);
+    for path in visitor.paths {
+        let set = map.entry(path).or_insert_with(HashSet::new);
+        set.insert(Property {
+            expr: String::new(),
+            is_method: true,
+            is_optional: false,
+            is_relm_widget: false,
+            name: method.to_string(),
+            parent: None,
+            widget_name: widget_name.clone(),
+        });
+    }
+}
+
+/*
+ * Get the name of the method updating the widgets of the condition or list in the placeholder.
+ */
+fn gen_update_method_name(placeholder: &Ident) -> Ident {
+    Ident::new(format!("__relm_update_{}", &placeholder.as_ref()["__relm_".len()..]))
+}
+
+/*
+ * Check whether the value of a property is a tuple, whose elements are the arguments of a setter
+ * with several arguments.
+ */
+fn is_tuple(value: &str) -> bool {
+    if let Ok(Expr { node: TupExpr(_), .. }) = parse_expr(value) {
+        true
     }
+    else {
+        false
+    }
+}
+
+/*
+ * Get the arguments of a property setter: a tuple is passed as multiple arguments, as in
+ * `size_request: (200, 100)`.
+ */
+fn gen_setter_args(value: &str) -> Tokens {
+    if let Ok(Expr { node: TupExpr(ref elements), .. }) = parse_expr(value) {
+        return quote! {
+            #(#elements),*
+        };
+    }
+    let mut tokens = Tokens::new();
+    tokens.append(value);
+    tokens
 }
 
-fn get_return_type(sig: MethodSig) -> Ty {
+fn get_return_type(sig: MethodSig) -> Result<Ty, String> {
     if let FunctionRetTy::Ty(ty) = sig.decl.output {
-        ty
+        Ok(ty)
     }
     else {
-        panic!("Unexpected default, expecting Ty");
+        Err("Expected the model() method to return the model, as in `fn model() -> Model`".to_string())
     }
 }
 
-fn get_second_param_type(sig: &MethodSig) -> Ty {
-    if let Captured(_, ref path) = sig.decl.inputs[1] {
-        path.clone()
+fn get_second_param_type(sig: &MethodSig) -> Result<Ty, String> {
+    if let Some(&Captured(_, ref path)) = sig.decl.inputs.get(1) {
+        Ok(path.clone())
     }
     else {
-        panic!("Unexpected `{:?}`, expecting Captured Ty", sig.decl.inputs[1]);
+        Err("Expected the message as second parameter of the update() method, as in \
+            `fn update(&mut self, event: Msg)`".to_string())
     }
 }
 
 fn gen_set_child_prop_calls(widget: &Widget) -> Option<ImplItem> {
     let mut tokens = Tokens::new();
     let widget_name = &widget.name;
+    // The type of the parent is unknown here, so the child properties are set from their name.
     for (key, value) in &widget.child_properties {
-        let property_func = Ident::new(format!("set_child_{}", key));
+        let property_name = key.replace('_', "-");
         tokens.append(quote! {
-            parent.#property_func(&self.#widget_name, #value);
+            ::relm::gtk_ext::set_child_property(&parent, &self.#widget_name, #property_name, &(#value));
         });
     }
     if !widget.child_properties.is_empty() {
         Some(block_to_impl_item(quote! {
             fn on_add<W: ::gtk::IsA<::gtk::Widget> + ::gtk::IsA<::gtk::Object>>(&self, parent: W) {
-                let parent: gtk::Box = ::gtk::Cast::downcast(::gtk::Cast::upcast::<::gtk::Widget>(parent))
-                    .expect("the parent of a widget with child properties must be a gtk::Box");
+                let parent: ::gtk::Container =
+                    ::gtk::Cast::downcast(::gtk::Cast::upcast::<::gtk::Widget>(parent))
+                    .expect("the parent of a widget with child properties must be a gtk::Container");
                 #tokens
             }
         }))
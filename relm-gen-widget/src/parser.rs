@@ -32,14 +32,37 @@ use syn::ItemKind::Mac;
 use syn::Lit::Str;
 use syn::StrStyle::Cooked;
 use syn::TokenTree::{self, Token};
-use syn::Token::{At, Colon, Comma, Eq, FatArrow, Gt, Ident, Literal, Lt, ModSep, Pound};
+use syn::Token::{At, Colon, Comma, Dollar, Eq, EqEq, FatArrow, Ge, Gt, Ident, Literal, Lt, ModSep, Not, Pound, RArrow};
 
+use diagnostics::{did_you_mean, report_error, show_token};
+use glade;
 use self::DefaultParam::*;
 use self::EventValue::*;
 use self::EventValueReturn::*;
 use self::EitherWidget::*;
 
+// The attributes which can be put on a widget.
+const ATTRIBUTES: &[&str] = &["container", "name", "parent"];
+// The modifiers of an accelerator, whose case is ignored by gtk.
+const MODIFIERS: &[&str] = &["Alt", "Control", "Ctl", "Ctrl", "Hyper", "Meta", "Mod1", "Mod2", "Mod3", "Mod4",
+    "Mod5", "Primary", "Release", "Shft", "Shift", "Super"];
+// The gdk names of the keys used in accelerators, except the letters, the digits and the function keys.
+const KEY_NAMES: &[&str] = &["BackSpace", "Begin", "Break", "Cancel", "Caps_Lock", "Clear", "Delete", "Down",
+    "End", "Escape", "Execute", "Find", "Help", "Home", "ISO_Left_Tab", "Insert", "KP_0", "KP_1", "KP_2", "KP_3",
+    "KP_4", "KP_5", "KP_6", "KP_7", "KP_8", "KP_9", "KP_Add", "KP_Begin", "KP_Decimal", "KP_Delete", "KP_Divide",
+    "KP_Down", "KP_End", "KP_Enter", "KP_Equal", "KP_Home", "KP_Insert", "KP_Left", "KP_Multiply", "KP_Next",
+    "KP_Page_Down", "KP_Page_Up", "KP_Prior", "KP_Right", "KP_Separator", "KP_Space", "KP_Subtract", "KP_Tab",
+    "KP_Up", "Left", "Linefeed", "Menu", "Next", "Num_Lock", "Page_Down", "Page_Up", "Pause", "Print", "Prior",
+    "Redo", "Return", "Right", "Scroll_Lock", "Select", "Sys_Req", "Tab", "Undo", "Up", "ampersand",
+    "apostrophe", "asciicircum", "asciitilde", "asterisk", "at", "backslash", "bar", "braceleft", "braceright",
+    "bracketleft", "bracketright", "colon", "comma", "dollar", "equal", "exclam", "grave", "greater", "less",
+    "minus", "numbersign", "parenleft", "parenright", "percent", "period", "plus", "question", "quotedbl",
+    "semicolon", "slash", "space", "underscore"];
+// The properties which can be bound to a model field with `bind`.
+const BOUND_PROPERTIES: &[&str] = &["active", "text", "value"];
+
 lazy_static! {
+    static ref FRAGMENTS: Mutex<HashMap<String, Fragment>> = Mutex::new(HashMap::new());
     static ref NAMES_INDEX: Mutex<HashMap<String, u32>> = Mutex::new(HashMap::new());
 }
 
@@ -58,10 +81,69 @@ pub enum EventValueReturn {
 
 #[derive(Debug)]
 pub enum EventValue {
+    Async(AsyncHandler),
     CurrentWidget(EventValueReturn),
     ForeignWidget(Tokens, EventValueReturn),
 }
 
+/*
+ * A future created by an event, as in `clicked => async { fetch(url) } -> (Loaded, Failed)`: it is
+ * spawned on the tokio thread and its result is sent as a message. The errors are ignored when there
+ * is no failure message.
+ */
+#[derive(Debug)]
+pub struct AsyncHandler {
+    pub body: Tokens,
+    pub failure: Option<Tokens>,
+    pub params: Vec<syn::Ident>,
+    pub success: Tokens,
+}
+
+#[derive(Debug)]
+pub struct Accel {
+    pub accelerator: Tokens,
+    pub value: Tokens,
+}
+
+#[derive(Debug)]
+pub struct Action {
+    pub accel: Option<Tokens>,
+    pub action_name: String,
+    pub name: syn::Ident,
+    pub prefix: String,
+    pub properties: HashMap<String, Tokens>,
+    pub value: Tokens,
+}
+
+/*
+ * A two-way binding, as in `text: bind model.name => NameChanged`: the model is updated when the
+ * property is changed by the user, and then the optional message is emitted.
+ */
+#[derive(Debug)]
+pub struct Binding {
+    pub model_path: Tokens,
+    pub property: String,
+    pub value: Option<Tokens>,
+}
+
+/*
+ * The branches of an `if` in the view, in order. The last branch has no condition when there is an
+ * `else`.
+ */
+pub struct Condition {
+    pub branches: Vec<(Option<Tokens>, Widget)>,
+}
+
+/*
+ * A `for` in the view: one relm widget is created for every item of items, identified by key.
+ */
+pub struct List {
+    pub item: syn::Ident,
+    pub items: Tokens,
+    pub key: Tokens,
+    pub widget: Box<Widget>,
+}
+
 #[derive(Debug)]
 pub struct Event {
     pub model_ident: Option<syn::Ident>,
@@ -79,11 +161,44 @@ impl Event {
     }
 }
 
+/*
+ * A signal connected by its name, as in `signal "notify::position"(paned: gtk::Paned, _) => Msg`: the
+ * parameters with a type are converted from a glib::Value, the others are given as a &glib::Value.
+ */
+#[derive(Debug)]
+pub struct Signal {
+    pub event: Event,
+    pub name: String,
+    pub param_types: Vec<Option<Tokens>>,
+}
+
+/*
+ * A widget tree declared with fragment! in the impl, which is inlined where it is used in the view.
+ */
+#[derive(Clone)]
+struct Fragment {
+    body: Vec<TokenTree>,
+    params: Vec<syn::Ident>,
+}
+
+/*
+ * A property declared with props! in the impl of a child widget: the parent sets it by sending the
+ * message to the child.
+ */
+#[derive(Debug)]
+pub struct Prop {
+    pub message: Tokens,
+    pub name: syn::Ident,
+    pub typ: Tokens,
+}
+
 pub struct Widget {
     pub child_properties: HashMap<String, Tokens>,
     pub children: Vec<Widget>,
+    pub condition: Option<Condition>,
     pub container_type: Option<Option<String>>,
     pub init_parameters: Vec<Tokens>,
+    pub list: Option<List>,
     pub name: syn::Ident,
     pub parent_id: Option<String>,
     pub properties: HashMap<String, Tokens>,
@@ -92,15 +207,17 @@ pub struct Widget {
 }
 
 impl Widget {
-    fn new_gtk(widget: GtkWidget, typ: Path, init_parameters: Vec<Tokens>, children: Vec<Widget>,
+    pub fn new_gtk(widget: GtkWidget, typ: Path, init_parameters: Vec<Tokens>, children: Vec<Widget>,
         properties: HashMap<String, Tokens>, child_properties: HashMap<String, Tokens>) -> Self
     {
         let name = gen_widget_name(&typ);
         Widget {
             child_properties,
             children,
+            condition: None,
             container_type: None,
             init_parameters,
+            list: None,
             name: syn::Ident::new(name),
             parent_id: None,
             properties,
@@ -120,8 +237,10 @@ impl Widget {
         Widget {
             child_properties,
             children,
+            condition: None,
             container_type: None,
             init_parameters,
+            list: None,
             name: syn::Ident::new(name),
             parent_id: None,
             properties,
@@ -139,17 +258,42 @@ pub enum EitherWidget {
 
 #[derive(Debug)]
 pub struct GtkWidget {
+    pub accels: Vec<Accel>,
+    pub actions: Vec<Action>,
+    pub bindings: Vec<Binding>,
+    // The properties given to g_object_new(), as in `gtk::Box(orientation = Vertical)`.
+    pub construct_properties: Vec<(String, Tokens)>,
     pub events: HashMap<String, Event>,
+    // The child properties of a glade file, converted from their text at runtime.
+    pub glade_child_properties: Vec<(String, String)>,
+    // The properties of a glade file, converted from their text at runtime, since their type is only
+    // known by GObject.
+    pub glade_properties: Vec<(String, String)>,
+    // The widget is created by its parent and retrieved with this name, as in a glade
+    // `<child internal-child="action_area">`.
+    pub internal_child: Option<String>,
     pub relm_name: Option<Ty>,
+    // The button emits this response of its dialog when clicked, as in the glade `<action-widgets>`.
+    pub response: Option<i32>,
     pub save: bool,
+    pub signals: Vec<Signal>,
 }
 
 impl GtkWidget {
-    fn new() -> Self {
+    pub fn new() -> Self {
         GtkWidget {
+            accels: vec![],
+            actions: vec![],
+            bindings: vec![],
+            construct_properties: vec![],
             events: HashMap::new(),
+            glade_child_properties: vec![],
+            glade_properties: vec![],
+            internal_child: None,
             relm_name: None,
+            response: None,
             save: false,
+            signals: vec![],
         }
     }
 }
@@ -179,69 +323,225 @@ fn last_segment_lowercase(path: &Path) -> bool {
     }
 }
 
-pub fn parse(tokens: &[TokenTree]) -> Widget {
+pub fn parse(tokens: &[TokenTree]) -> Result<Widget, String> {
     let tokens =
-        if let Token(Literal(Str(ref relm_view_file, _))) = tokens[0] {
-            // TODO: also support glade file.
-            let mut file = File::open(relm_view_file).expect("File::open() in parse()");
+        if let Some(&Token(Literal(Str(ref relm_view_file, _)))) = tokens.first() {
+            if relm_view_file.ends_with(".glade") || relm_view_file.ends_with(".ui") {
+                return glade::parse(relm_view_file, &tokens[1..]);
+            }
             let mut file_content = String::new();
-            file.read_to_string(&mut file_content).expect("read_to_string() in parse()");
-            let item = parse_item(&file_content).expect("parse_item() in parse()");
+            File::open(relm_view_file)
+                .and_then(|mut file| file.read_to_string(&mut file_content))
+                .map_err(|error| format!("Cannot read view file {}: {}", relm_view_file, error))?;
+            let item = parse_item(&file_content)
+                .map_err(|error| format!("{} in view file {}", error, relm_view_file))?;
             if let Mac(syn::Mac { tts, .. }) = item.node {
-                if let TokenTree::Delimited(Delimited { ref tts, .. }) = tts[0] {
+                if let Some(&TokenTree::Delimited(Delimited { ref tts, .. })) = tts.first() {
                     tts.clone()
                 }
                 else {
-                    panic!("Expected delimited macro")
+                    return Err(format!("Expected delimited macro in view file {}", relm_view_file));
                 }
             }
             else {
-                panic!("Expecting a macro")
+                return Err(format!("Expected a macro in view file {}", relm_view_file));
             }
         }
         else {
             tokens.to_vec()
         };
-    let (mut widget, _, parent_id) = parse_child(&tokens);
+    let (mut widget, _, parent_id) = parse_child(&tokens)?;
     widget.parent_id = parent_id;
-    widget
+    Ok(widget)
 }
 
-fn parse_widget(tokens: &[TokenTree], save: bool) -> (Widget, &[TokenTree]) {
-    let (gtk_type, mut tokens) = parse_qualified_name(tokens);
+/*
+ * Parse the fragments declared in a fragment! item of the impl:
+ * fragment! {
+ *     name($param1, $param2) {
+ *         Widget { property: $param1 }
+ *     }
+ * }
+ */
+pub fn add_fragments(tokens: &[TokenTree]) -> Result<(), String> {
+    let mut tokens =
+        match tokens.first() {
+            Some(&TokenTree::Delimited(Delimited { ref tts, .. })) => &tts[..],
+            _ => return Err("Expected `{` after fragment! in impl".to_string()),
+        };
+    let mut new_fragments = vec![];
+    while !tokens.is_empty() {
+        let (name, new_tokens) = parse_ident(tokens)?;
+        let params =
+            match new_tokens.first() {
+                Some(&TokenTree::Delimited(Delimited { delim: Paren, ref tts })) => parse_fragment_params(tts)?,
+                _ => return Err(format!("Expected `(` after fragment `{}` in fragment! macro", name)),
+            };
+        let body =
+            match new_tokens.get(1) {
+                Some(&TokenTree::Delimited(Delimited { delim: Brace, ref tts })) => tts.clone(),
+                _ => return Err(format!("Expected `{{` after the parameters of fragment `{}` in fragment! macro",
+                    name)),
+            };
+        new_fragments.push((name, Fragment {
+            body,
+            params,
+        }));
+        tokens = &new_tokens[2..];
+        if tokens.first() == Some(&Token(Comma)) {
+            tokens = &tokens[1..];
+        }
+    }
+    let mut fragments = FRAGMENTS.lock().expect("lock fragments");
+    for (name, fragment) in new_fragments {
+        if fragments.contains_key(&name) {
+            report_error(format!("The fragment `{}` is declared twice in fragment! macro", name));
+        }
+        fragments.insert(name, fragment);
+    }
+    Ok(())
+}
+
+/*
+ * The fragments are only available in the impl where they are declared.
+ */
+pub fn clear_fragments() {
+    FRAGMENTS.lock().expect("lock fragments").clear();
+}
+
+/*
+ * Parse the properties of a props! item:
+ * props! {
+ *     name: Type => Message,
+ * }
+ */
+pub fn parse_props(tokens: &[TokenTree]) -> Result<Vec<Prop>, String> {
+    let mut tokens =
+        match tokens.first() {
+            Some(&TokenTree::Delimited(Delimited { ref tts, .. })) => &tts[..],
+            _ => return Err("Expected `{` after props! in impl".to_string()),
+        };
+    let mut props = vec![];
+    while !tokens.is_empty() {
+        let (name, new_tokens) = parse_ident(tokens)?;
+        if new_tokens.first() != Some(&Token(Colon)) {
+            return Err(format!("Expected `:` after property `{}` in props! macro", name));
+        }
+        tokens = &new_tokens[1..];
+        let mut typ = Tokens::new();
+        while let Some(token) = tokens.first() {
+            if *token == Token(FatArrow) {
+                break;
+            }
+            token.to_tokens(&mut typ);
+            tokens = &tokens[1..];
+        }
+        if tokens.is_empty() {
+            return Err(format!("Expected `=> Message` after the type of property `{}` in props! macro", name));
+        }
+        let (message, new_tokens) = parse_value(&tokens[1..]);
+        props.push(Prop {
+            message,
+            name: syn::Ident::new(name),
+            typ,
+        });
+        tokens = new_tokens;
+        if tokens.first() == Some(&Token(Comma)) {
+            tokens = &tokens[1..];
+        }
+    }
+    Ok(props)
+}
+
+pub fn parse_widget(tokens: &[TokenTree], save: bool) -> Result<(Widget, &[TokenTree]), String> {
+    let (gtk_type, mut tokens) = parse_qualified_name(tokens)?;
     let mut gtk_widget = GtkWidget::new();
     let mut init_parameters = vec![];
     let mut children = vec![];
     let mut properties = HashMap::new();
     let mut child_properties = HashMap::new();
     gtk_widget.save = save;
-    if let TokenTree::Delimited(Delimited { delim: Paren, ref tts }) = tokens[0] {
-        let parameters = parse_comma_list(tts);
-        init_parameters = parameters;
+    if let TokenTree::Delimited(Delimited { delim: Paren, ref tts }) = *first_token(tokens)? {
+        if is_construct_property(tts) {
+            gtk_widget.construct_properties = parse_construct_properties(tts)?;
+        }
+        else {
+            init_parameters = parse_comma_list(tts);
+        }
         tokens = &tokens[1..];
     }
-    if let TokenTree::Delimited(Delimited { delim: Brace, ref tts }) = tokens[0] {
+    if let TokenTree::Delimited(Delimited { delim: Brace, ref tts }) = *first_token(tokens)? {
         let mut tts = &tts[..];
         while !tts.is_empty() {
-            if tts[0] == Token(Pound) || try_parse_name(tts).is_some() {
-                let (child, new_tts, _) = parse_child(tts);
+            if tts[0] == Token(Ident(syn::Ident::new("if"))) {
+                let (child, new_tts) = parse_condition(&tts[1..])?;
                 tts = new_tts;
                 children.push(child);
             }
+            else if tts[0] == Token(Ident(syn::Ident::new("for"))) {
+                let (child, new_tts) = parse_list(&tts[1..])?;
+                tts = new_tts;
+                children.push(child);
+            }
+            else if tts[0] == Token(Pound) || try_parse_name(tts).is_some() || is_fragment_call(tts) {
+                let (child, new_tts, _) = parse_child(tts)?;
+                tts = new_tts;
+                children.push(child);
+            }
+            else if let Some((child, new_tts)) = try_parse_menu(tts)? {
+                tts = new_tts;
+                children.push(child);
+            }
+            else if is_keyword(tts, "accel") {
+                let (accel, new_tts) = parse_accel(&tts[1..])?;
+                gtk_widget.accels.push(accel);
+                tts = new_tts;
+            }
+            else if is_keyword(tts, "action") {
+                let (action, new_tts) = parse_action(&tts[1..])?;
+                gtk_widget.actions.push(action);
+                tts = new_tts;
+            }
+            else if is_keyword(tts, "signal") {
+                let (signal, new_tts) = parse_signal(&tts[1..])?;
+                gtk_widget.signals.push(signal);
+                tts = new_tts;
+            }
+            else if let Token(Literal(Str(_, _))) = tts[0] {
+                let (signal, new_tts) = parse_signal(tts)?;
+                gtk_widget.signals.push(signal);
+                tts = new_tts;
+            }
             else {
                 // Property or event.
-                let (ident, _) = parse_ident(tts);
-                tts = &tts[1..];
-                match tts[0] {
+                let (ident, new_tts) = parse_ident(tts)?;
+                tts = new_tts;
+                match *first_token(tts)? {
+                    Token(Colon) if is_binding(&tts[1..]) => {
+                        let (binding, new_tts) = parse_binding(&tts[2..], ident.clone())?;
+                        // The property is also set from the model like any other property.
+                        let model_path = &binding.model_path;
+                        let value =
+                            if ident == "text" {
+                                quote! { &#model_path }
+                            }
+                            else {
+                                quote! { #model_path }
+                            };
+                        properties.insert(ident, value);
+                        gtk_widget.bindings.push(binding);
+                        tts = new_tts;
+                    },
                     Token(Colon) => {
-                        tts = parse_value_or_child_properties(tts, ident, &mut child_properties, &mut properties);
+                        tts = parse_value_or_child_properties(tts, ident, &mut child_properties, &mut properties)?;
                     },
                     TokenTree::Delimited(Delimited { delim: Paren, .. }) | Token(FatArrow) => {
-                        let (event, new_tts) = parse_event(tts, DefaultOneParam);
+                        let (event, new_tts) = parse_event(tts, DefaultOneParam)?;
                         gtk_widget.events.insert(ident, event);
                         tts = new_tts;
                     },
-                    _ => panic!("Expected `:` or `(` but found `{:?}` in view! macro", tts[0]),
+                    ref token => return Err(format!("Expected `:`, `=>` or `(` after `{}` but found `{}`{} in view! \
+                        macro", ident, show_token(token), fat_arrow_suggestion(token))),
                 }
             }
 
@@ -251,45 +551,665 @@ fn parse_widget(tokens: &[TokenTree], save: bool) -> (Widget, &[TokenTree]) {
         }
     }
     else {
-        panic!("Expected {{ but found `{:?}` in view! macro", tokens[0]);
+        return Err(format!("Expected {{ but found `{}` in view! macro", show_token(&tokens[0])));
     }
     let widget = Widget::new_gtk(gtk_widget, gtk_type, init_parameters, children, properties, child_properties);
-    (widget, &tokens[1..])
+    Ok((widget, &tokens[1..]))
+}
+
+/*
+ * Get the next token of the view, which ends too soon when there is none.
+ */
+fn first_token(tokens: &[TokenTree]) -> Result<&TokenTree, String> {
+    tokens.first().ok_or_else(|| "Unexpected end of view! macro".to_string())
+}
+
+/*
+ * A keyword is an identifier followed by a string literal, as in `accel "<Primary>s" => Save`, so
+ * that it does not clash with properties and events of the same name.
+ */
+fn is_keyword(tokens: &[TokenTree], keyword: &str) -> bool {
+    if let Some(&Token(Literal(Str(_, _)))) = tokens.get(1) {
+        tokens[0] == Token(Ident(syn::Ident::new(keyword)))
+    }
+    else {
+        false
+    }
+}
+
+/*
+ * A binding starts with `bind` followed by the model field, so that a value named bind can still be
+ * used.
+ */
+fn is_binding(tokens: &[TokenTree]) -> bool {
+    if let Some(&Token(Ident(_))) = tokens.get(1) {
+        tokens[0] == Token(Ident(syn::Ident::new("bind")))
+    }
+    else {
+        false
+    }
+}
+
+fn parse_binding(tokens: &[TokenTree], property: String) -> Result<(Binding, &[TokenTree]), String> {
+    if !BOUND_PROPERTIES.contains(&property.as_str()) {
+        return Err(format!("Two-way binding is not supported for property `{}`{} in view! macro: expected \
+            `text`, `active` or `value`", property, did_you_mean(&property, BOUND_PROPERTIES)));
+    }
+    let mut model_path = Tokens::new();
+    let mut i = 0;
+    while i < tokens.len() && tokens[i] != Token(Comma) && tokens[i] != Token(FatArrow) {
+        tokens[i].to_tokens(&mut model_path);
+        i += 1;
+    }
+    let mut tokens = &tokens[i..];
+    let value =
+        if tokens.first() == Some(&Token(FatArrow)) {
+            let (value, new_tokens) = parse_fat_arrow_value(tokens)?;
+            tokens = new_tokens;
+            Some(value)
+        }
+        else {
+            None
+        };
+    let binding = Binding {
+        model_path: model_path,
+        property: property,
+        value: value,
+    };
+    Ok((binding, tokens))
+}
+
+fn parse_string(tokens: &[TokenTree]) -> Result<(String, Tokens, &[TokenTree]), String> {
+    match *first_token(tokens)? {
+        Token(Literal(Str(ref string, _))) => {
+            let mut literal = Tokens::new();
+            tokens[0].to_tokens(&mut literal);
+            Ok((string.clone(), literal, &tokens[1..]))
+        },
+        ref token => Err(format!("Expected string literal but found `{}` in view! macro", show_token(token))),
+    }
+}
+
+fn parse_fat_arrow_value(tokens: &[TokenTree]) -> Result<(Tokens, &[TokenTree]), String> {
+    Ok(parse_value(expect_fat_arrow(tokens)?))
+}
+
+/*
+ * Skip the `=>` before a message, with a suggestion for the tokens that look like it.
+ */
+fn expect_fat_arrow(tokens: &[TokenTree]) -> Result<&[TokenTree], String> {
+    match tokens.first() {
+        Some(&Token(FatArrow)) => Ok(&tokens[1..]),
+        Some(token) => Err(format!("Expected `=>` before the message but found `{}`{} in view! macro, as in \
+                                   `clicked => Message`", show_token(token), fat_arrow_suggestion(token))),
+        None => Err("Expected `=>` followed by a message in view! macro, as in `clicked => Message`".to_string()),
+    }
+}
+
+fn fat_arrow_suggestion(token: &TokenTree) -> &'static str {
+    match *token {
+        Token(Eq) | Token(EqEq) | Token(Ge) | Token(Gt) | Token(RArrow) => " (did you mean `=>`?)",
+        _ => "",
+    }
+}
+
+fn parse_accel(tokens: &[TokenTree]) -> Result<(Accel, &[TokenTree]), String> {
+    let (accelerator_string, accelerator, tokens) = parse_string(tokens)?;
+    check_accelerator(&accelerator_string)?;
+    let (value, tokens) = parse_fat_arrow_value(tokens)?;
+    let accel = Accel {
+        accelerator: accelerator,
+        value: value,
+    };
+    Ok((accel, tokens))
+}
+
+fn parse_action(tokens: &[TokenTree]) -> Result<(Action, &[TokenTree]), String> {
+    let (detailed_name, _, mut tokens) = parse_string(tokens)?;
+    let (prefix, action_name) =
+        if let Some(index) = detailed_name.find('.') {
+            (detailed_name[..index].to_string(), detailed_name[index + 1..].to_string())
+        }
+        else {
+            ("win".to_string(), detailed_name.clone())
+        };
+    check_action_name(&prefix, &action_name)?;
+    let mut properties = HashMap::new();
+    if let Some(&TokenTree::Delimited(Delimited { delim: Paren, ref tts })) = tokens.first() {
+        properties = parse_child_properties(tts)?;
+        tokens = &tokens[1..];
+    }
+    let accel = properties.remove("accel");
+    if let Some(ref accel) = accel {
+        // The accelerator can be any expression, so only a literal is checked.
+        if let Ok(tts) = syn::parse_token_trees(accel.as_str()) {
+            if let (1, Some(&Token(Literal(Str(ref accelerator, _))))) = (tts.len(), tts.first()) {
+                check_accelerator(accelerator)?;
+            }
+        }
+    }
+    let (value, tokens) = parse_fat_arrow_value(tokens)?;
+    let action = Action {
+        accel,
+        name: syn::Ident::new(gen_action_name(&action_name)),
+        action_name,
+        prefix,
+        properties,
+        value,
+    };
+    Ok((action, tokens))
+}
+
+/*
+ * The prefix and the name of an action are used in the names of the generated variables, so they
+ * can only contain the characters allowed by both Rust and GIO.
+ */
+fn check_action_name(prefix: &str, action_name: &str) -> Result<(), String> {
+    // A digit in base 36 is an ASCII letter or digit.
+    if prefix.is_empty() || !prefix.chars().all(|chr| chr.is_digit(36) || chr == '_') {
+        return Err(format!("Invalid prefix `{}` for action \"{}\" in view! macro: expected letters, digits \
+            or `_`, as in \"app.{}\"", prefix, action_name, action_name));
+    }
+    if action_name.is_empty() || !action_name.chars().all(|chr| chr.is_digit(36) || chr == '-') {
+        return Err(format!("Invalid name `{}` for action \"{}.{}\" in view! macro: expected letters, digits \
+            or `-`", action_name, prefix, action_name));
+    }
+    Ok(())
+}
+
+/*
+ * Check the modifiers and the key of an accelerator like "<Primary><Shift>s", which gtk would
+ * silently ignore when they are wrong.
+ */
+fn check_accelerator(accelerator: &str) -> Result<(), String> {
+    let mut rest = accelerator;
+    while rest.starts_with('<') {
+        let end = rest.find('>')
+            .ok_or_else(|| format!("Expected `>` after the modifier in accelerator \"{}\" in view! macro",
+                accelerator))?;
+        let modifier = &rest[1..end];
+        if !MODIFIERS.iter().any(|name| name.to_lowercase() == modifier.to_lowercase()) {
+            return Err(format!("Unknown modifier <{}>{} in accelerator \"{}\" in view! macro", modifier,
+                did_you_mean(modifier, MODIFIERS), accelerator));
+        }
+        rest = &rest[end + 1..];
+    }
+    if rest.is_empty() {
+        return Err(format!("Expected a key after the modifiers in accelerator \"{}\" in view! macro, as in \
+            \"<Primary>s\"", accelerator));
+    }
+    if !is_key_name(rest) {
+        // A short key is close to too many names for a suggestion to help.
+        let suggestion = if rest.len() > 3 { did_you_mean(rest, KEY_NAMES) } else { String::new() };
+        return Err(format!("Unknown key `{}`{} in accelerator \"{}\" in view! macro: expected a letter, a digit \
+            or a gdk key name like `Page_Up`", rest, suggestion, accelerator));
+    }
+    Ok(())
+}
+
+/*
+ * A key is a letter, a digit, a function key like F5 or another gdk key name.
+ * The names of the multimedia keys, like XF86AudioPlay, are not checked.
+ */
+fn is_key_name(key: &str) -> bool {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(chr), None) => return chr.is_digit(36),
+        (Some('F'), Some(_)) => {
+            if let Ok(number) = key[1..].parse::<u32>() {
+                return number >= 1 && number <= 35;
+            }
+        },
+        _ => (),
+    }
+    KEY_NAMES.contains(&key) || (key.starts_with("XF86") && key.len() > 4)
+}
+
+/*
+ * Parse `if condition { widget } else if condition { widget } else { widget }`.
+ * The branches are rendered in a gtk::Box placeholder which keeps the position of the condition in
+ * its parent.
+ */
+fn parse_condition(mut tokens: &[TokenTree]) -> Result<(Widget, &[TokenTree]), String> {
+    let mut branches = vec![];
+    loop {
+        let mut condition = Tokens::new();
+        while let Some(token) = tokens.first() {
+            if let TokenTree::Delimited(Delimited { delim: Brace, .. }) = *token {
+                break;
+            }
+            token.to_tokens(&mut condition);
+            tokens = &tokens[1..];
+        }
+        let (widget, new_tokens) = parse_branch(tokens)?;
+        check_branch(&widget)?;
+        branches.push((Some(condition), widget));
+        tokens = new_tokens;
+        if tokens.first() != Some(&Token(Ident(syn::Ident::new("else")))) {
+            break;
+        }
+        tokens = &tokens[1..];
+        if tokens.first() == Some(&Token(Ident(syn::Ident::new("if")))) {
+            tokens = &tokens[1..];
+        }
+        else {
+            let (widget, new_tokens) = parse_branch(tokens)?;
+            check_branch(&widget)?;
+            branches.push((None, widget));
+            tokens = new_tokens;
+            break;
+        }
+    }
+    let typ = parse_path("gtk::Box").expect("condition path");
+    let mut widget = Widget::new_gtk(GtkWidget::new(), typ, vec![], vec![], HashMap::new(), HashMap::new());
+    widget.name = syn::Ident::new(gen_name("__relm_condition"));
+    widget.condition = Some(Condition {
+        branches,
+    });
+    Ok((widget, tokens))
+}
+
+/*
+ * The widgets of a branch are created and destroyed with the branch, which is not supported for the
+ * widgets managed by a condition, a for or the container of a relm widget.
+ */
+fn check_branch(widget: &Widget) -> Result<(), String> {
+    if widget.condition.is_some() {
+        return Err("A condition cannot be nested in a branch of another condition in view! macro".to_string());
+    }
+    if widget.list.is_some() {
+        return Err("A for cannot be used in a branch of a condition in view! macro".to_string());
+    }
+    if widget.container_type.is_some() {
+        let segments: Vec<_> = widget.typ.segments.iter().map(|segment| segment.ident.as_ref()).collect();
+        return Err(format!("The #[container] attribute cannot be used in a branch of a condition, but found it \
+            on `{}` in view! macro", segments.join("::")));
+    }
+    for child in &widget.children {
+        check_branch(child)?;
+    }
+    Ok(())
+}
+
+/*
+ * Parse `for item in items, key = key { Widget(params) }`.
+ * Like the branches of a condition, the widgets are created in a gtk::Box placeholder.
+ */
+fn parse_list(mut tokens: &[TokenTree]) -> Result<(Widget, &[TokenTree]), String> {
+    let in_keyword = Token(Ident(syn::Ident::new("in")));
+    let key_keyword = Token(Ident(syn::Ident::new("key")));
+    let item =
+        match (tokens.get(0), tokens.get(1)) {
+            (Some(&Token(Ident(ref item))), Some(token)) if *token == in_keyword => item.clone(),
+            _ => return Err("Expected `for item in` in view! macro".to_string()),
+        };
+    tokens = &tokens[2..];
+    let mut items = Tokens::new();
+    while let Some(token) = tokens.first() {
+        if *token == Token(Comma) && tokens.get(1) == Some(&key_keyword) && tokens.get(2) == Some(&Token(Eq)) {
+            tokens = &tokens[3..];
+            break;
+        }
+        if let TokenTree::Delimited(Delimited { delim: Brace, .. }) = *token {
+            return Err("Expected `, key = ` after the items of the for in view! macro".to_string());
+        }
+        token.to_tokens(&mut items);
+        tokens = &tokens[1..];
+    }
+    let mut key = Tokens::new();
+    while let Some(token) = tokens.first() {
+        if let TokenTree::Delimited(Delimited { delim: Brace, .. }) = *token {
+            break;
+        }
+        token.to_tokens(&mut key);
+        tokens = &tokens[1..];
+    }
+    let (child, tokens) = parse_branch(tokens)?;
+    if let Gtk(_) = child.widget {
+        return Err(format!("Expected a relm widget in the for but found `{}` in view! macro",
+            path_to_string(&child.typ)));
+    }
+    if !child.children.is_empty() {
+        return Err("The widget of a for cannot have children in view! macro".to_string());
+    }
+    // The widgets of the list are added to the parent, which is saved as a gtk::Container to update them.
+    let typ = parse_path("gtk::Container").expect("list path");
+    let mut widget = Widget::new_gtk(GtkWidget::new(), typ, vec![], vec![], HashMap::new(), HashMap::new());
+    widget.name = syn::Ident::new(gen_name("__relm_list"));
+    widget.list = Some(List {
+        item: item,
+        items: items,
+        key: key,
+        widget: Box::new(child),
+    });
+    Ok((widget, tokens))
+}
+
+fn parse_branch(tokens: &[TokenTree]) -> Result<(Widget, &[TokenTree]), String> {
+    match tokens.first() {
+        Some(&TokenTree::Delimited(Delimited { delim: Brace, ref tts })) if !tts.is_empty() => {
+            let (widget, rest, _) = parse_child(tts)?;
+            if !rest.is_empty() && rest != [Token(Comma)] {
+                return Err(format!("Expected only one widget in a branch but found `{}` in view! macro",
+                    show_token(&rest[0])));
+            }
+            Ok((widget, &tokens[1..]))
+        },
+        _ => Err("Expected a widget between `{` and `}` in view! macro".to_string()),
+    }
+}
+
+/*
+ * Parse a menu or a menu entry:
+ * menu { entries }
+ * item "Label"(properties) => Msg
+ * submenu "Label"(properties) { entries }
+ * separator
+ * They are turned into gtk::Menu, gtk::MenuItem and gtk::SeparatorMenuItem widgets.
+ */
+fn try_parse_menu(tokens: &[TokenTree]) -> Result<Option<(Widget, &[TokenTree])>, String> {
+    let keyword =
+        if let Some(&Token(Ident(ref ident))) = tokens.first() {
+            ident.as_ref().to_string()
+        }
+        else {
+            return Ok(None);
+        };
+    match keyword.as_ref() {
+        "menu" => {
+            if let Some(&TokenTree::Delimited(Delimited { delim: Brace, ref tts })) = tokens.get(1) {
+                Ok(Some((parse_menu(tts)?, &tokens[2..])))
+            }
+            else {
+                Ok(None)
+            }
+        },
+        "item" | "submenu" => {
+            match tokens.get(1) {
+                Some(&Token(Literal(Str(_, _)))) | Some(&TokenTree::Delimited(Delimited { delim: Paren, .. })) =>
+                    parse_menu_item(&tokens[1..], keyword == "submenu").map(Some),
+                _ => Ok(None),
+            }
+        },
+        "separator" => {
+            match tokens.get(1) {
+                None | Some(&Token(Comma)) => {
+                    let typ = parse_path("gtk::SeparatorMenuItem").expect("separator path");
+                    let widget = Widget::new_gtk(GtkWidget::new(), typ, vec![], vec![], HashMap::new(),
+                        HashMap::new());
+                    Ok(Some((widget, &tokens[1..])))
+                },
+                _ => Ok(None),
+            }
+        },
+        _ => Ok(None),
+    }
+}
+
+fn parse_menu(mut tokens: &[TokenTree]) -> Result<Widget, String> {
+    let mut children = vec![];
+    while !tokens.is_empty() {
+        match try_parse_menu(tokens)? {
+            Some((child, new_tokens)) => {
+                children.push(child);
+                tokens = new_tokens;
+            },
+            None => return Err(format!("Expected `item`, `submenu` or `separator` but found `{}` in menu",
+                show_token(&tokens[0]))),
+        }
+
+        if tokens.first() == Some(&Token(Comma)) {
+            tokens = &tokens[1..];
+        }
+    }
+    let typ = parse_path("gtk::Menu").expect("menu path");
+    Ok(Widget::new_gtk(GtkWidget::new(), typ, vec![], children, HashMap::new(), HashMap::new()))
 }
 
-fn parse_child(mut tokens: &[TokenTree]) -> (Widget, &[TokenTree], Option<String>) {
+fn parse_menu_item(mut tokens: &[TokenTree], is_submenu: bool) -> Result<(Widget, &[TokenTree]), String> {
+    let mut properties = HashMap::new();
+    let mut gtk_widget = GtkWidget::new();
+    let mut children = vec![];
+    if let Token(Literal(Str(_, _))) = tokens[0] {
+        let (_, label, new_tokens) = parse_string(tokens)?;
+        properties.insert("label".to_string(), label);
+        tokens = new_tokens;
+    }
+    if let Some(&TokenTree::Delimited(Delimited { delim: Paren, ref tts })) = tokens.first() {
+        properties.extend(parse_child_properties(tts)?);
+        tokens = &tokens[1..];
+    }
+    if is_submenu {
+        match tokens.first() {
+            Some(&TokenTree::Delimited(Delimited { delim: Brace, ref tts })) => {
+                children.push(parse_menu(tts)?);
+                tokens = &tokens[1..];
+            },
+            _ => return Err("Expected `{` after submenu in view! macro".to_string()),
+        }
+    }
+    else {
+        let (event, new_tokens) = parse_event(tokens, DefaultOneParam)?;
+        gtk_widget.events.insert("activate".to_string(), event);
+        tokens = new_tokens;
+    }
+    let typ = parse_path("gtk::MenuItem").expect("menu item path");
+    let widget = Widget::new_gtk(gtk_widget, typ, vec![], children, properties, HashMap::new());
+    Ok((widget, tokens))
+}
+
+fn parse_child(mut tokens: &[TokenTree]) -> Result<(Widget, &[TokenTree], Option<String>), String> {
     let (mut attributes, new_tokens) = parse_attributes(tokens);
     let container_type = attributes.remove("container")
         .map(|typ| typ.map(str::to_string));
     tokens = new_tokens;
     let name = attributes.get("name").and_then(|name| *name);
     let (mut widget, new_tokens) =
-        if tokens.get(1) == Some(&Token(ModSep)) {
-            parse_widget(tokens, name.is_some())
+        if is_fragment_call(tokens) {
+            let (mut widget, new_tokens) = parse_fragment_call(tokens)?;
+            if let Gtk(ref mut gtk_widget) = widget.widget {
+                gtk_widget.save = gtk_widget.save || name.is_some();
+            }
+            (widget, new_tokens)
+        }
+        else if tokens.get(1) == Some(&Token(ModSep)) {
+            parse_widget(tokens, name.is_some())?
         }
         else {
-            parse_relm_widget(tokens)
+            parse_relm_widget(tokens)?
         };
     if let Some(name) = name {
         widget.name = syn::Ident::new(name);
     }
     widget.container_type = container_type;
     let parent_id = attributes.get("parent").and_then(|opt_str| opt_str.map(str::to_string));
-    (widget, new_tokens, parent_id)
+    Ok((widget, new_tokens, parent_id))
+}
+
+/*
+ * A fragment is used like a macro call: `name!(arguments)`.
+ */
+fn is_fragment_call(tokens: &[TokenTree]) -> bool {
+    match (tokens.get(0), tokens.get(1), tokens.get(2)) {
+        (Some(&Token(Ident(_))), Some(&Token(Not)), Some(&TokenTree::Delimited(Delimited { delim: Paren, .. }))) =>
+            true,
+        _ => false,
+    }
+}
+
+/*
+ * Inline a fragment: its parameters are replaced by the tokens of the arguments before the widget is
+ * parsed, so that the model attributes used in the arguments are bound like anywhere else in the view.
+ */
+fn parse_fragment_call(tokens: &[TokenTree]) -> Result<(Widget, &[TokenTree]), String> {
+    let (name, _) = parse_ident(tokens)?;
+    let args =
+        if let TokenTree::Delimited(Delimited { ref tts, .. }) = tokens[2] {
+            split_fragment_args(tts)
+        }
+        else {
+            unreachable!("is_fragment_call() checks the arguments");
+        };
+    // The fragment is removed while it is inlined, so that it cannot use itself.
+    let fragment = FRAGMENTS.lock().expect("lock fragments").remove(&name);
+    let fragment =
+        match fragment {
+            Some(fragment) => fragment,
+            None => {
+                let names: Vec<_> = FRAGMENTS.lock().expect("lock fragments").keys()
+                    .map(|name| format!("{}!", name))
+                    .collect();
+                let names: Vec<_> = names.iter().map(String::as_str).collect();
+                return Err(format!("Unknown fragment `{}!`{} in view! macro: fragments are declared with \
+                    fragment! in the impl and cannot use themselves", name,
+                    did_you_mean(&format!("{}!", name), &names)));
+            },
+        };
+    // Put the fragment back before checking the call, so that it stays available after an error.
+    let result = inline_fragment(&name, &fragment, &args);
+    FRAGMENTS.lock().expect("lock fragments").insert(name, fragment);
+    Ok((result?, &tokens[3..]))
+}
+
+/*
+ * Split the arguments of a fragment call on the commas which separate expressions or types, so that
+ * the commas of `HashMap<K, V>` or `foo::<A, B>()` do not split an argument.
+ * The arguments are split on every comma when they are not all expressions or types.
+ */
+fn split_fragment_args(tokens: &[TokenTree]) -> Vec<&[TokenTree]> {
+    split_exprs(tokens).unwrap_or_else(|| {
+        tokens.split(|token| *token == Token(Comma))
+            .filter(|arg| !arg.is_empty())
+            .collect()
+    })
+}
+
+/*
+ * Try the commas from the first one, so that an argument which parses only up to a comma, like the
+ * comparison `a < b`, is kept separate from the next one.
+ */
+fn split_exprs(tokens: &[TokenTree]) -> Option<Vec<&[TokenTree]>> {
+    if tokens.is_empty() {
+        return Some(vec![]);
+    }
+    let ends = tokens.iter().enumerate()
+        .filter(|&(_, token)| *token == Token(Comma))
+        .map(|(index, _)| index)
+        .chain(Some(tokens.len()));
+    for end in ends {
+        let arg = &tokens[..end];
+        if is_expr_or_type(arg) {
+            let rest = if end < tokens.len() { &tokens[end + 1..] } else { &[] };
+            if let Some(mut args) = split_exprs(rest) {
+                args.insert(0, arg);
+                return Some(args);
+            }
+        }
+    }
+    None
+}
+
+fn is_expr_or_type(tokens: &[TokenTree]) -> bool {
+    let mut string = Tokens::new();
+    for token in tokens {
+        token.to_tokens(&mut string);
+    }
+    syn::parse_expr(string.as_str()).is_ok() || syn::parse_type(string.as_str()).is_ok()
+}
+
+fn inline_fragment(name: &str, fragment: &Fragment, args: &[&[TokenTree]]) -> Result<Widget, String> {
+    if args.len() != fragment.params.len() {
+        return Err(format!("The fragment `{}!` takes {} arguments but {} were given in view! macro", name,
+            fragment.params.len(), args.len()));
+    }
+    let body = substitute_params(&fragment.body, &fragment.params, args)?;
+    if body.is_empty() {
+        return Err(format!("Expected a widget in fragment `{}` in fragment! macro", name));
+    }
+    let (widget, rest, _) = parse_child(&body)?;
+    if !rest.is_empty() && rest != [Token(Comma)] {
+        return Err(format!("Expected only one widget in fragment `{}` but found `{}` in fragment! macro", name,
+            show_token(&rest[0])));
+    }
+    Ok(widget)
+}
+
+fn parse_fragment_params(tokens: &[TokenTree]) -> Result<Vec<syn::Ident>, String> {
+    tokens.split(|token| *token == Token(Comma))
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            if param.len() == 2 && param[0] == Token(Dollar) {
+                if let Token(Ident(ref ident)) = param[1] {
+                    return Ok(ident.clone());
+                }
+            }
+            Err(format!("Expected a parameter like `$name` but found `{}` in fragment! macro", show_token(&param[0])))
+        })
+        .collect()
+}
+
+/*
+ * Replace the parameters by their arguments, between parentheses so that `$a * 2` with the argument
+ * `1 + 1` is `(1 + 1) * 2`.
+ * The argument of `bind` is not put between parentheses, since it is a model field, not a value.
+ */
+fn substitute_params(tokens: &[TokenTree], params: &[syn::Ident], args: &[&[TokenTree]])
+    -> Result<Vec<TokenTree>, String>
+{
+    let mut result = vec![];
+    let mut index = 0;
+    while index < tokens.len() {
+        match tokens[index] {
+            TokenTree::Delimited(Delimited { delim, ref tts }) => {
+                result.push(TokenTree::Delimited(Delimited {
+                    delim,
+                    tts: substitute_params(tts, params, args)?,
+                }));
+            },
+            Token(Dollar) => {
+                if let Some(&Token(Ident(ref ident))) = tokens.get(index + 1) {
+                    let position =
+                        match params.iter().position(|param| param == ident) {
+                            Some(position) => position,
+                            None => return Err(format!("Unknown parameter `${}` in fragment! macro", ident)),
+                        };
+                    if result.last() == Some(&Token(Ident(syn::Ident::new("bind")))) {
+                        result.extend_from_slice(args[position]);
+                    }
+                    else {
+                        result.push(TokenTree::Delimited(Delimited {
+                            delim: Paren,
+                            tts: args[position].to_vec(),
+                        }));
+                    }
+                    index += 1;
+                }
+                else {
+                    result.push(tokens[index].clone());
+                }
+            },
+            ref token => result.push(token.clone()),
+        }
+        index += 1;
+    }
+    Ok(result)
 }
 
-fn parse_ident(tokens: &[TokenTree]) -> (String, &[TokenTree]) {
-    match tokens[0] {
+fn parse_ident(tokens: &[TokenTree]) -> Result<(String, &[TokenTree]), String> {
+    match *first_token(tokens)? {
         Token(Ident(ref ident)) => {
-            (ident.to_string(), &tokens[1..])
+            Ok((ident.to_string(), &tokens[1..]))
         },
-        _ => panic!("Expected ident but found `{:?}` in view! macro", tokens[0]),
+        ref token => Err(format!("Expected ident but found `{}` in view! macro", show_token(token))),
     }
 }
 
-fn parse_qualified_name(tokens: &[TokenTree]) -> (Path, &[TokenTree]) {
-    try_parse_name(tokens)
-        .unwrap_or_else(|| panic!("Expected qualified name but found `{:?}` in view! macro", tokens[0]))
+fn parse_qualified_name(tokens: &[TokenTree]) -> Result<(Path, &[TokenTree]), String> {
+    match try_parse_name(tokens) {
+        Some(name) => Ok(name),
+        None => Err(format!("Expected qualified name but found `{}` in view! macro",
+            show_token(first_token(tokens)?))),
+    }
 }
 
 fn try_parse_name(mut tokens: &[TokenTree]) -> Option<(Path, &[TokenTree])> {
@@ -308,8 +1228,8 @@ fn try_parse_name(mut tokens: &[TokenTree]) -> Option<(Path, &[TokenTree])> {
         path_string.push_str(&toks.to_string());
         tokens = &tokens[1..];
     }
-    match tokens[0] {
-        TokenTree::Delimited(_) | Token(Comma) => {
+    match tokens.first() {
+        Some(&TokenTree::Delimited(_)) | Some(&Token(Comma)) => {
             if let Ok(path) = parse_path(&path_string) {
                 if !last_segment_lowercase(&path) {
                     return Some((path, tokens));
@@ -321,7 +1241,7 @@ fn try_parse_name(mut tokens: &[TokenTree]) -> Option<(Path, &[TokenTree])> {
     None
 }
 
-fn parse_comma_ident_list(tokens: &[TokenTree]) -> Vec<syn::Ident> {
+fn parse_comma_ident_list(tokens: &[TokenTree]) -> Result<Vec<syn::Ident>, String> {
     let mut params = vec![];
     for token in tokens {
         if *token != Token(Comma) {
@@ -331,11 +1251,39 @@ fn parse_comma_ident_list(tokens: &[TokenTree]) -> Vec<syn::Ident> {
                 params.push(syn::Ident::new(tokens.as_str()));
             }
             else {
-                panic!("Expecting Token, but found: `{:?}`", token);
+                return Err(format!("Expected a parameter but found `{}` in view! macro", show_token(token)));
             }
         }
     }
-    params
+    Ok(params)
+}
+
+fn is_construct_property(tokens: &[TokenTree]) -> bool {
+    match (tokens.get(0), tokens.get(1)) {
+        (Some(&Token(Ident(_))), Some(&Token(Eq))) => true,
+        _ => false,
+    }
+}
+
+/*
+ * Parse the construct properties: `name = value, name = value`.
+ */
+fn parse_construct_properties(tokens: &[TokenTree]) -> Result<Vec<(String, Tokens)>, String> {
+    tokens.split(|token| *token == Token(Comma))
+        .filter(|property| !property.is_empty())
+        .map(|property| {
+            if !is_construct_property(property) {
+                return Err(format!("Expected `name = value` but found `{}` in the construct properties in view! \
+                    macro", show_token(&property[0])));
+            }
+            let (name, _) = parse_ident(property)?;
+            let mut value = Tokens::new();
+            for token in &property[2..] {
+                token.to_tokens(&mut value);
+            }
+            Ok((name, value))
+        })
+        .collect()
 }
 
 fn parse_comma_list(tokens: &[TokenTree]) -> Vec<Tokens> {
@@ -354,37 +1302,52 @@ fn parse_comma_list(tokens: &[TokenTree]) -> Vec<Tokens> {
     params
 }
 
-fn parse_event(mut tokens: &[TokenTree], default_param: DefaultParam) -> (Event, &[TokenTree]) {
+/*
+ * Check if the token after the name of an event starts it: its parameters, the `=>` or, for a
+ * message without parameters, the `with model`.
+ */
+fn is_event_start(token: &TokenTree) -> bool {
+    match *token {
+        TokenTree::Delimited(Delimited { delim: Paren, .. }) | Token(FatArrow) => true,
+        Token(Ident(ref with)) => with.as_ref() == "with",
+        _ => false,
+    }
+}
+
+fn parse_event(mut tokens: &[TokenTree], default_param: DefaultParam) -> Result<(Event, &[TokenTree]), String> {
     let mut event = Event::new();
     if default_param == DefaultNoParam {
         event.params.clear();
     }
-    if let TokenTree::Delimited(Delimited { delim: Paren, ref tts }) = tokens[0] {
-        event.params = parse_comma_ident_list(tts);
+    if let Some(&TokenTree::Delimited(Delimited { delim: Paren, ref tts })) = tokens.first() {
+        event.params = parse_comma_ident_list(tts)?;
         tokens = &tokens[1..];
     }
     event.model_ident =
-        if tokens[0] == Token(Ident(syn::Ident::new("with"))) {
-            if let Token(Ident(ref ident)) = tokens[1] {
+        if tokens.first() == Some(&Token(Ident(syn::Ident::new("with")))) {
+            if let Some(&Token(Ident(ref ident))) = tokens.get(1) {
                 tokens = &tokens[2..];
                 Some(ident.clone())
             }
             else {
-                panic!("Expecting ident after `with`, but found `{:?}` in view! macro", tokens[1]);
+                return Err(format!("Expected ident after `with` but found `{}` in view! macro",
+                    tokens.get(1).map(show_token).unwrap_or_default()));
             }
         }
         else {
             None
         };
-    if tokens[0] != Token(FatArrow) {
-        panic!("Expected `=>` but found `{:?}` in view! macro", tokens[0]);
-    }
-    tokens = &tokens[1..];
+    tokens = expect_fat_arrow(tokens)?;
     event.value =
+        if is_async(tokens) {
+            let (handler, new_tokens) = parse_async(&tokens[1..])?;
+            tokens = new_tokens;
+            Async(handler)
+        }
         // Message sent to another widget.
-        if tokens.len() >= 2 && tokens[1] == Token(At) {
-            let (event_value, new_tokens) = parse_event_value(&tokens[2..]);
-            let (ident, _) = parse_ident(tokens);
+        else if tokens.len() >= 2 && tokens[1] == Token(At) {
+            let (event_value, new_tokens) = parse_event_value(&tokens[2..])?;
+            let (ident, _) = parse_ident(tokens)?;
             tokens = new_tokens;
             let mut ident_tokens = Tokens::new();
             ident_tokens.append(ident);
@@ -392,47 +1355,144 @@ fn parse_event(mut tokens: &[TokenTree], default_param: DefaultParam) -> (Event,
         }
         // Message sent to the same widget.
         else {
-            let (event_value, new_tokens) = parse_event_value(tokens);
+            let (event_value, new_tokens) = parse_event_value(tokens)?;
             tokens = new_tokens;
             CurrentWidget(event_value)
         };
-    (event, tokens)
+    Ok((event, tokens))
+}
+
+/*
+ * Parse a signal connected by its name: `"notify::position"(paned: gtk::Paned, _) => Msg`.
+ */
+fn parse_signal(tokens: &[TokenTree]) -> Result<(Signal, &[TokenTree]), String> {
+    let (name, _, mut tokens) = parse_string(tokens)?;
+    let mut params = vec![];
+    let mut param_types = vec![];
+    if let Some(&TokenTree::Delimited(Delimited { delim: Paren, ref tts })) = tokens.first() {
+        let mut tts = &tts[..];
+        while !tts.is_empty() {
+            params.push(syn::Ident::new(show_token(&tts[0])));
+            tts = &tts[1..];
+            if tts.first() == Some(&Token(Colon)) {
+                let (typ, new_tts) = parse_value(&tts[1..]);
+                param_types.push(Some(typ));
+                tts = new_tts;
+            }
+            else {
+                param_types.push(None);
+            }
+            match tts.first() {
+                Some(&Token(Comma)) => tts = &tts[1..],
+                Some(token) => return Err(format!("Expected `,` or `:` after the parameter of signal \"{}\" but \
+                    found `{}` in view! macro", name, show_token(token))),
+                None => (),
+            }
+        }
+        tokens = &tokens[1..];
+    }
+    let (mut event, tokens) = parse_event(tokens, DefaultNoParam)?;
+    event.params = params;
+    let signal = Signal {
+        event,
+        name,
+        param_types,
+    };
+    Ok((signal, tokens))
+}
+
+fn is_async(tokens: &[TokenTree]) -> bool {
+    match (tokens.get(0), tokens.get(1)) {
+        (Some(&Token(Ident(ref ident))), Some(&TokenTree::Delimited(Delimited { delim, .. }))) =>
+            ident == "async" && (delim == Brace || delim == Paren),
+        _ => false,
+    }
 }
 
-fn parse_event_value(tokens: &[TokenTree]) -> (EventValueReturn, &[TokenTree]) {
-    if Token(Ident(syn::Ident::new("return"))) == tokens[0] {
+/*
+ * Parse the future of an event after `async`: `(handle) { future } -> (Msg, FailMsg)`.
+ */
+fn parse_async(mut tokens: &[TokenTree]) -> Result<(AsyncHandler, &[TokenTree]), String> {
+    let mut params = vec![];
+    if let TokenTree::Delimited(Delimited { delim: Paren, ref tts }) = tokens[0] {
+        params = parse_comma_ident_list(tts)?;
+        if params.len() > 1 {
+            return Err("Expected only the handle as parameter of `async` in view! macro, as in \
+                `async(handle) { future }`".to_string());
+        }
+        tokens = &tokens[1..];
+    }
+    let mut body = Tokens::new();
+    match tokens.first() {
+        Some(token @ &TokenTree::Delimited(Delimited { delim: Brace, .. })) => token.to_tokens(&mut body),
+        token => return Err(format!("Expected `{{` after `async` but found `{}` in view! macro",
+            token.map(show_token).unwrap_or_default())),
+    }
+    tokens = &tokens[1..];
+    match tokens.first() {
+        Some(&Token(RArrow)) => tokens = &tokens[1..],
+        token => return Err(format!("Expected `->` before the message of the future but found `{}` in view! \
+            macro, as in `async {{ future }} -> Msg`", token.map(show_token).unwrap_or_default())),
+    }
+    let (success, failure, tokens) =
+        if let TokenTree::Delimited(Delimited { delim: Paren, ref tts }) = *first_token(tokens)? {
+            let (success, new_tts) = parse_value(tts);
+            if new_tts.first() != Some(&Token(Comma)) {
+                return Err("Expected `,` after the success message of the future in view! macro, as in \
+                    `async { future } -> (Msg, FailMsg)`".to_string());
+            }
+            let (failure, _) = parse_value(&new_tts[1..]);
+            (success, Some(failure), &tokens[1..])
+        }
+        else {
+            let (success, tokens) = parse_value(tokens);
+            (success, None, tokens)
+        };
+    let handler = AsyncHandler {
+        body,
+        failure,
+        params,
+        success,
+    };
+    Ok((handler, tokens))
+}
+
+fn parse_event_value(tokens: &[TokenTree]) -> Result<(EventValueReturn, &[TokenTree]), String> {
+    if Token(Ident(syn::Ident::new("return"))) == *first_token(tokens)? {
         let (value, tokens) = parse_value(&tokens[1..]);
-        (CallReturn(value), tokens)
+        Ok((CallReturn(value), tokens))
     }
     else if let TokenTree::Delimited(Delimited { delim: Paren, ref tts }) = tokens[0] {
         let (value1, new_tts) = parse_value(tts);
-        if new_tts[0] != Token(Comma) {
-            panic!("Expected `,` but found `{:?}` in view! macro", new_tts[0]);
+        if new_tts.first() != Some(&Token(Comma)) {
+            return Err(format!("Expected `,` but found `{}` in view! macro",
+                new_tts.first().map(show_token).unwrap_or_default()));
         }
         let (value2, _) = parse_value(&new_tts[1..]);
-        (Return(value1, value2), &tokens[1..])
+        Ok((Return(value1, value2), &tokens[1..]))
     }
     else {
         let (value, tokens) = parse_value(tokens);
-        (WithoutReturn(value), tokens)
+        Ok((WithoutReturn(value), tokens))
     }
 }
 
 fn parse_value_or_child_properties<'a>(tokens: &'a [TokenTree], ident: String,
-    child_properties: &mut HashMap<String, Tokens>, properties: &mut HashMap<String, Tokens>) -> &'a [TokenTree]
+    child_properties: &mut HashMap<String, Tokens>, properties: &mut HashMap<String, Tokens>)
+    -> Result<&'a [TokenTree], String>
 {
-    match tokens[1] {
+    match *first_token(&tokens[1..])? {
         TokenTree::Delimited(Delimited { delim: Brace, tts: ref child_tokens }) => {
-            let props = parse_child_properties(child_tokens);
+            let props = parse_child_properties(child_tokens)?;
             for (key, value) in props {
                 child_properties.insert(key, value);
             }
-            &tokens[2..]
+            Ok(&tokens[2..])
         },
         _ => {
             let (value, tts) = parse_value(&tokens[1..]);
             properties.insert(ident, value);
-            tts
+            Ok(tts)
         },
     }
 }
@@ -465,6 +1525,17 @@ fn gen_widget_name(path: &Path) -> String {
     format!("{}{}", name, index)
 }
 
+fn gen_action_name(action_name: &str) -> String {
+    gen_name(&format!("action_{}", action_name.replace('-', "_")))
+}
+
+fn gen_name(name: &str) -> String {
+    let mut hashmap = NAMES_INDEX.lock().expect("lock() in gen_name()");
+    let index = hashmap.entry(name.to_string()).or_insert(0);
+    *index += 1;
+    format!("{}{}", name, index)
+}
+
 fn path_to_string(path: &Path) -> String {
     let mut string = String::new();
     for segment in &path.segments {
@@ -475,15 +1546,15 @@ fn path_to_string(path: &Path) -> String {
 
 fn parse_attributes(mut tokens: &[TokenTree]) -> (HashMap<&str, Option<&str>>, &[TokenTree]) {
     let mut attributes = HashMap::new();
-    while tokens[0] == Token(Pound) {
+    while tokens.first() == Some(&Token(Pound)) {
         tokens = &tokens[1..];
-        if let TokenTree::Delimited(Delimited { delim: Bracket, ref tts }) = tokens[0] {
+        if let Some(&TokenTree::Delimited(Delimited { delim: Bracket, ref tts })) = tokens.first() {
             tokens = &tokens[1..];
-            if let Token(Ident(ref ident)) = tts[0] {
+            if let Some(&Token(Ident(ref ident))) = tts.first() {
                 let name = ident.as_ref();
                 let value =
                     if let Some(&Token(Eq)) = tts.get(1) {
-                        if let Token(Literal(Str(ref name, Cooked))) = tts[2] {
+                        if let Some(&Token(Literal(Str(ref name, Cooked)))) = tts.get(2) {
                             Some(name.as_str())
                         }
                         else {
@@ -493,6 +1564,15 @@ fn parse_attributes(mut tokens: &[TokenTree]) -> (HashMap<&str, Option<&str>>, &
                     else {
                         None
                     };
+                if !ATTRIBUTES.contains(&name) {
+                    report_error(format!("Unknown attribute #[{}]{} in view! macro: expected #[name=\"...\"], \
+                                         #[container], #[container=\"...\"] or #[parent=\"...\"]", name,
+                                         did_you_mean(name, ATTRIBUTES)));
+                }
+                else if name != "container" && value.is_none() {
+                    report_error(format!("The #[{}] attribute requires a string value, as in #[{}=\"value\"], in \
+                                         view! macro", name, name));
+                }
                 attributes.insert(name, value);
             }
         }
@@ -500,14 +1580,14 @@ fn parse_attributes(mut tokens: &[TokenTree]) -> (HashMap<&str, Option<&str>>, &
     (attributes, tokens)
 }
 
-fn parse_child_properties(mut tokens: &[TokenTree]) -> HashMap<String, Tokens> {
-    // TODO: panic if the same child properties is set twice.
+fn parse_child_properties(mut tokens: &[TokenTree]) -> Result<HashMap<String, Tokens>, String> {
+    // TODO: report an error if the same child properties is set twice.
     // TODO: same for normal properties?
     let mut properties = HashMap::new();
     while !tokens.is_empty() {
-        let (ident, _) = parse_ident(tokens);
-        tokens = &tokens[1..];
-        if let Token(Colon) = tokens[0] {
+        let (ident, new_tokens) = parse_ident(tokens)?;
+        tokens = new_tokens;
+        if tokens.first() == Some(&Token(Colon)) {
             tokens = &tokens[1..];
             let (value, new_tokens) = parse_value(tokens);
             tokens = new_tokens;
@@ -518,11 +1598,11 @@ fn parse_child_properties(mut tokens: &[TokenTree]) -> HashMap<String, Tokens> {
             tokens = &tokens[1..];
         }
     }
-    properties
+    Ok(properties)
 }
 
-fn parse_relm_widget(tokens: &[TokenTree]) -> (Widget, &[TokenTree]) {
-    let (relm_type, mut tokens) = parse_qualified_name(tokens);
+fn parse_relm_widget(tokens: &[TokenTree]) -> Result<(Widget, &[TokenTree]), String> {
+    let (relm_type, mut tokens) = parse_qualified_name(tokens)?;
     let mut relm_widget = RelmWidget::new();
     let mut init_parameters = vec![];
     let mut children = vec![];
@@ -533,41 +1613,58 @@ fn parse_relm_widget(tokens: &[TokenTree]) -> (Widget, &[TokenTree]) {
         init_parameters = parameters;
         tokens = &tokens[1..];
     }
-    if let TokenTree::Delimited(Delimited { delim: Brace, ref tts }) = tokens[0] {
+    if let Some(&TokenTree::Delimited(Delimited { delim: Brace, ref tts })) = tokens.first() {
+        tokens = &tokens[1..];
         let mut tts = &tts[..];
         while !tts.is_empty() {
             let is_child =
                 if let Some((_, next_tokens)) = try_parse_name(tts) {
-                    if let TokenTree::Delimited(Delimited { delim: Brace, .. }) = next_tokens[0] {
-                        true
-                    }
-                    else {
-                        false
+                    match (&next_tokens[0], next_tokens.get(1)) {
+                        (&TokenTree::Delimited(Delimited { delim: Brace, .. }), _) => true,
+                        // A child with model parameters, unlike an event with parameters, is not followed by
+                        // `=>`.
+                        (&TokenTree::Delimited(Delimited { delim: Paren, .. }), Some(&Token(FatArrow))) => false,
+                        (&TokenTree::Delimited(Delimited { delim: Paren, .. }), _) => true,
+                        _ => false,
                     }
                 }
                 else {
                     false
                 };
-            if tts[0] == Token(Pound) || is_child {
-                let (child, new_tts, _) = parse_child(tts);
+            if tts[0] == Token(Ident(syn::Ident::new("if"))) {
+                let (child, new_tts) = parse_condition(&tts[1..])?;
+                tts = new_tts;
+                children.push(child);
+            }
+            else if tts[0] == Token(Ident(syn::Ident::new("for"))) {
+                let (child, new_tts) = parse_list(&tts[1..])?;
+                tts = new_tts;
+                children.push(child);
+            }
+            else if tts[0] == Token(Pound) || is_child || is_fragment_call(tts) {
+                let (child, new_tts, _) = parse_child(tts)?;
                 tts = new_tts;
                 children.push(child);
             }
             else {
                 // Property or event.
-                let (ident, _) = parse_ident(tts);
-                tts = &tts[1..];
-                match tts[0] {
+                let (ident, new_tts) = parse_ident(tts)?;
+                tts = new_tts;
+                match *first_token(tts)? {
+                    Token(Colon) if is_binding(&tts[1..]) =>
+                        return Err(format!("Two-way binding with `bind` is only supported on gtk widgets, but found \
+                            it on property `{}` in view! macro", ident)),
                     Token(Colon) => {
-                        tts = parse_value_or_child_properties(tts, ident, &mut child_properties, &mut properties);
+                        tts = parse_value_or_child_properties(tts, ident, &mut child_properties, &mut properties)?;
                     },
-                    TokenTree::Delimited(Delimited { delim: Paren, .. }) | Token(FatArrow) => {
-                        let (event, new_tts) = parse_event(&tts[0..], DefaultNoParam);
-                        let mut entry = relm_widget.events.entry(ident).or_insert_with(Vec::new);
+                    ref token if is_event_start(token) => {
+                        let (event, new_tts) = parse_event(tts, DefaultNoParam)?;
+                        let entry = relm_widget.events.entry(ident).or_insert_with(Vec::new);
                         entry.push(event);
                         tts = new_tts;
                     },
-                    _ => panic!("Expected `:`, `=>` or `(` but found `{:?}` in view! macro", tts[0]),
+                    ref token => return Err(format!("Expected `:`, `=>` or `(` after `{}` but found `{}`{} in view! \
+                        macro", ident, show_token(token), fat_arrow_suggestion(token))),
                 }
             }
 
@@ -577,5 +1674,76 @@ fn parse_relm_widget(tokens: &[TokenTree]) -> (Widget, &[TokenTree]) {
         }
     }
     let widget = Widget::new_relm(relm_widget, relm_type, init_parameters, children, properties, child_properties);
-    (widget, &tokens[1..])
+    Ok((widget, tokens))
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::{Tokens, ToTokens};
+    use syn::{self, TokenTree, parse_token_trees};
+
+    use super::{check_accelerator, check_action_name, parse, split_fragment_args, substitute_params};
+
+    fn parse_error(view: &str) -> Option<String> {
+        parse(&parse_token_trees(view).expect("view tokens")).err()
+    }
+
+    fn show_tokens(tokens: &[TokenTree]) -> String {
+        let mut string = Tokens::new();
+        for token in tokens {
+            token.to_tokens(&mut string);
+        }
+        string.to_string()
+    }
+
+    #[test]
+    fn accelerators() {
+        assert_eq!(check_accelerator("<Primary><Shift>s"), Ok(()));
+        assert_eq!(check_accelerator("<ctrl>F5"), Ok(()));
+        assert_eq!(check_accelerator("<Ctrl>").err().map(|error| error.starts_with("Expected a key")), Some(true));
+        assert_eq!(check_accelerator("<Control"), Err("Expected `>` after the modifier in accelerator \
+            \"<Control\" in view! macro".to_string()));
+        assert_eq!(check_accelerator("<Contrl>q"), Err("Unknown modifier <Contrl> (did you mean `Control`?) in \
+            accelerator \"<Contrl>q\" in view! macro".to_string()));
+        assert_eq!(check_accelerator("<Primary>Page_Up"), Ok(()));
+        assert_eq!(check_accelerator("<Alt>XF86Back"), Ok(()));
+        assert_eq!(check_accelerator("<Primary>zz"), Err("Unknown key `zz` in accelerator \"<Primary>zz\" in \
+            view! macro: expected a letter, a digit or a gdk key name like `Page_Up`".to_string()));
+        assert_eq!(check_accelerator("<Primary>Escpe").err().map(|error| error.contains("did you mean `Escape`?")),
+            Some(true));
+        assert!(check_accelerator("F36").is_err());
+    }
+
+    #[test]
+    fn action_names() {
+        assert_eq!(check_action_name("app", "save-as"), Ok(()));
+        assert!(check_action_name("my-app", "quit").is_err());
+        assert!(check_action_name("", "quit").is_err());
+        assert!(check_action_name("app", "quit.now").is_err());
+        assert!(check_action_name("win", "").is_err());
+    }
+
+    #[test]
+    fn fragment_args() {
+        let args = parse_token_trees("HashMap<K, V>, foo::<A, B>(1), a < b, c > d,").expect("args");
+        let args: Vec<_> = split_fragment_args(&args).iter().map(|arg| show_tokens(arg)).collect();
+        assert_eq!(args, vec!["HashMap < K , V >", "foo :: < A , B > ( 1 )", "a < b", "c > d"]);
+        let body = parse_token_trees("text: $value * 2, value: bind $value").expect("body");
+        let arg = parse_token_trees("1 + 1").expect("arg");
+        let body = substitute_params(&body, &[syn::Ident::new("value")], &[&arg]).expect("substituted body");
+        assert_eq!(show_tokens(&body), "text : ( 1 + 1 ) * 2 , value : bind 1 + 1");
+    }
+
+    #[test]
+    fn branch_limits() {
+        assert_eq!(parse_error("gtk::Box { if model.a { gtk::Label {} } else { gtk::Box { gtk::Label {} } } }"), None);
+        assert_eq!(parse_error("gtk::Box { if model.a { gtk::Box { if model.b { gtk::Label {} } } } }"),
+            Some("A condition cannot be nested in a branch of another condition in view! macro".to_string()));
+        assert_eq!(parse_error("gtk::Box { if model.a { gtk::Box {} } else { gtk::Box { for item in model.items, \
+            key = item.id { Item(item) } } } }"),
+            Some("A for cannot be used in a branch of a condition in view! macro".to_string()));
+        assert_eq!(parse_error("gtk::Box { if model.a { #[container] gtk::Box {} } }"),
+            Some("The #[container] attribute cannot be used in a branch of a condition, but found it on `gtk::Box` \
+                in view! macro".to_string()));
+    }
 }
@@ -19,31 +19,214 @@
  * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
-use syn::{Expr, Ident};
-use syn::ExprKind::{Field, Path};
+use syn::{Block, Expr, Ident, Item};
+use syn::ExprKind::{Call, Closure, Field, Index, Paren, Path, Ret, Unary};
+use syn::UnOp::Deref;
 use syn::visit::{Visitor, walk_expr};
 
+/*
+ * Path of fields from the model, e.g. [user, name] for model.user.name.
+ * The empty path is the whole model.
+ */
+pub type ModelPath = Vec<Ident>;
+
 pub struct ModelVariableVisitor {
-    pub idents: Vec<Ident>,
+    pub paths: Vec<ModelPath>,
 }
 
 impl ModelVariableVisitor {
     pub fn new() -> Self {
         ModelVariableVisitor {
-            idents: vec![],
+            paths: vec![],
+        }
+    }
+
+    /*
+     * Visit the indices of a path like model.items[index], since they can use the model too.
+     */
+    fn visit_indices(&mut self, expr: &Expr) {
+        match expr.node {
+            Field(ref obj, _) | Paren(ref obj) | Unary(Deref, ref obj) => self.visit_indices(obj),
+            Index(ref obj, ref index) => {
+                self.visit_indices(obj);
+                self.visit_expr(index);
+            },
+            _ => (),
         }
     }
 }
 
 impl Visitor for ModelVariableVisitor {
     fn visit_expr(&mut self, expr: &Expr) {
-        if let Field(ref obj, ref field) = expr.node {
-            if let Path(_, ref path) = obj.node {
-                if path.segments[0].ident == Ident::new("model") {
-                    self.idents.push(field.clone());
+        if let Some(path) = get_model_path(expr) {
+            self.paths.push(path);
+            self.visit_indices(expr);
+        }
+        else {
+            walk_expr(self, expr);
+        }
+    }
+}
+
+/*
+ * Get the path of the model fields used by an expression like model.user.name.
+ * The fields after an index are not part of the path, since the index is only known at runtime.
+ */
+pub fn get_model_path(expr: &Expr) -> Option<ModelPath> {
+    get_partial_model_path(expr).map(|(path, _)| path)
+}
+
+fn get_partial_model_path(expr: &Expr) -> Option<(ModelPath, bool)> {
+    match expr.node {
+        Path(None, ref path) if path.segments.len() == 1 && path.segments[0].ident == Ident::new("model") =>
+            Some((vec![], true)),
+        Field(ref obj, ref field) => {
+            get_partial_model_path(obj).map(|(mut path, complete)| {
+                if complete {
+                    path.push(field.clone());
+                }
+                (path, complete)
+            })
+        },
+        Index(ref obj, _) => get_partial_model_path(obj).map(|(path, _)| (path, false)),
+        Paren(ref obj) | Unary(Deref, ref obj) => get_partial_model_path(obj),
+        _ => None,
+    }
+}
+
+/*
+ * Check if a change to one of these paths changes the other, i.e. when one is a prefix of the other.
+ */
+pub fn paths_overlap(path1: &[Ident], path2: &[Ident]) -> bool {
+    path1.starts_with(path2) || path2.starts_with(path1)
+}
+
+/*
+ * Check if the block contains a return expression, ignoring the closures and the nested items.
+ */
+pub fn has_return(block: &Block) -> bool {
+    let mut visitor = ReturnVisitor {
+        has_return: false,
+    };
+    for stmt in &block.stmts {
+        visitor.visit_stmt(stmt);
+    }
+    visitor.has_return
+}
+
+struct ReturnVisitor {
+    has_return: bool,
+}
+
+impl Visitor for ReturnVisitor {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr.node {
+            Closure(..) => (),
+            Ret(_) => self.has_return = true,
+            _ => walk_expr(self, expr),
+        }
+    }
+
+    fn visit_item(&mut self, _item: &Item) {
+    }
+}
+
+/*
+ * Check if the expression reads a variable, like title or self.title, instead of only using
+ * constants, like Vertical or gtk::Align::Center, ignoring the closures.
+ * The functions called are not variables, so that tr("Title") is a constant.
+ */
+pub fn reads_variable(expr: &Expr) -> bool {
+    let mut visitor = VariableVisitor {
+        reads_variable: false,
+    };
+    visitor.visit_expr(expr);
+    visitor.reads_variable
+}
+
+struct VariableVisitor {
+    reads_variable: bool,
+}
+
+impl Visitor for VariableVisitor {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr.node {
+            Call(_, ref args) => {
+                for arg in args {
+                    self.visit_expr(arg);
+                }
+            },
+            Closure(..) => (),
+            Path(None, ref path) if path.segments.len() == 1 => {
+                let first_char = path.segments[0].ident.as_ref().chars().next();
+                if first_char.map(|chr| chr.is_lowercase() || chr == '_') == Some(true) {
+                    self.reads_variable = true;
                 }
-            }
+            },
+            _ => walk_expr(self, expr),
         }
-        walk_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::{Ident, parse_expr};
+    use syn::visit::Visitor;
+
+    use super::{ModelPath, ModelVariableVisitor, get_model_path, paths_overlap, reads_variable};
+
+    fn path(fields: &str) -> ModelPath {
+        fields.split('.').filter(|field| !field.is_empty()).map(Ident::new).collect()
+    }
+
+    fn model_path(expr: &str) -> Option<ModelPath> {
+        get_model_path(&parse_expr(expr).expect("parse_expr() in model_path"))
+    }
+
+    #[test]
+    fn overlapping_paths() {
+        assert!(paths_overlap(&path("user.name"), &path("user.name")));
+        assert!(paths_overlap(&path("user"), &path("user.name")));
+        assert!(paths_overlap(&path("user.name"), &path("user")));
+        // The empty path is the whole model.
+        assert!(paths_overlap(&path(""), &path("user.name")));
+        assert!(!paths_overlap(&path("user.name"), &path("user.age")));
+        assert!(!paths_overlap(&path("user"), &path("username")));
+    }
+
+    #[test]
+    fn model_paths() {
+        assert_eq!(model_path("model"), Some(path("")));
+        assert_eq!(model_path("model.user.name"), Some(path("user.name")));
+        assert_eq!(model_path("(*model).counter"), Some(path("counter")));
+        // The fields after an index are not part of the path.
+        assert_eq!(model_path("model.users[index].name"), Some(path("users")));
+        assert_eq!(model_path("other.user"), None);
+        assert_eq!(model_path("model.user.len()"), None);
+    }
+
+    #[test]
+    fn paths_used_by_an_expression() {
+        let expr = parse_expr("model.user.name.clone() + &model.items[model.index].to_string()")
+            .expect("parse_expr() in paths_used_by_an_expression");
+        let mut visitor = ModelVariableVisitor::new();
+        visitor.visit_expr(&expr);
+        assert_eq!(visitor.paths.len(), 3);
+        assert!(visitor.paths.contains(&path("user.name")));
+        assert!(visitor.paths.contains(&path("items")));
+        assert!(visitor.paths.contains(&path("index")));
+    }
+
+    #[test]
+    fn variables() {
+        let reads = |expr| reads_variable(&parse_expr(expr).expect("parse_expr() in variables"));
+        assert!(reads("&title"));
+        assert!(reads("&self.title.to_string()"));
+        assert!(reads("Some(count + 1)"));
+        assert!(!reads("Vertical"));
+        assert!(!reads("gtk::Align::Center"));
+        assert!(!reads("(200, -1)"));
+        assert!(!reads("&tr(\"Title\")"));
+        assert!(!reads("|value| value + 1"));
     }
 }
@@ -0,0 +1,118 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use gdk;
+use gio::{self, ActionExt, ActionMapExt};
+use glib::translate::ToGlibPtr;
+use gtk::{self, Inhibit, IsA, WidgetExt};
+use gtk_sys;
+
+/// A named action that can be activated from an accelerator, a button or a menu item.
+///
+/// The widgets implementing `gtk::Actionable` can use it by setting their action name to
+/// `"prefix.name"` (see [`ActionableExtManual`](gtk_ext/trait.ActionableExtManual.html)).
+#[derive(Clone)]
+pub struct Action {
+    action: gio::SimpleAction,
+}
+
+impl Action {
+    /// Activate the action if it is enabled.
+    pub fn activate(&self) {
+        if self.action.get_enabled() {
+            self.action.activate(None);
+        }
+    }
+
+    /// Get the underlying `gio::SimpleAction`.
+    pub fn action(&self) -> &gio::SimpleAction {
+        &self.action
+    }
+
+    /// Check whether the action can be activated.
+    pub fn is_enabled(&self) -> bool {
+        self.action.get_enabled()
+    }
+
+    /// Set whether the action can be activated.
+    /// The widgets using this action are made insensitive when it is disabled.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.action.set_enabled(enabled);
+    }
+}
+
+/// A group of actions added to a widget under a prefix, like `win` or `app`.
+#[derive(Clone)]
+pub struct ActionGroup {
+    group: gio::SimpleActionGroup,
+}
+
+impl ActionGroup {
+    /// Create a new action group and insert it in `widget` under the `prefix` name.
+    /// The actions of this group are available to the widget and its children.
+    pub fn new<W: IsA<gtk::Widget>>(widget: &W, prefix: &str) -> Self {
+        let group = gio::SimpleActionGroup::new();
+        unsafe {
+            gtk_sys::gtk_widget_insert_action_group(widget.to_glib_none().0, prefix.to_glib_none().0,
+                group.to_glib_none().0 as *mut _);
+        }
+        ActionGroup {
+            group: group,
+        }
+    }
+
+    /// Add an action named `name` to this group, calling `callback` when activated.
+    pub fn add<CALLBACK: Fn() + 'static>(&self, name: &str, callback: CALLBACK) -> Action {
+        let action = gio::SimpleAction::new(name, None);
+        let _ = action.connect_activate(move |_, _| callback());
+        self.group.add_action(&action);
+        Action {
+            action: action,
+        }
+    }
+}
+
+/// Call `callback` when the `accelerator` is pressed in `widget`.
+///
+/// The accelerator uses the format of `gtk::accelerator_parse()`, for instance `"<Primary>s"`.
+///
+/// ## Note
+/// The widget must receive the key events: the accelerators are usually connected to the window.
+pub fn connect_accel<W, CALLBACK>(widget: &W, accelerator: &str, callback: CALLBACK)
+    where W: IsA<gtk::Widget> + WidgetExt,
+          CALLBACK: Fn() + 'static,
+{
+    let (key, modifiers) = gtk::accelerator_parse(accelerator);
+    if key == 0 {
+        warn!("Invalid accelerator {}", accelerator);
+        return;
+    }
+    let _ = widget.connect_key_press_event(move |_, event| {
+        let state = event.get_state() & gtk::accelerator_get_default_mod_mask();
+        if gdk::keyval_to_lower(event.get_keyval()) == key && state == modifiers {
+            callback();
+            Inhibit(true)
+        }
+        else {
+            Inhibit(false)
+        }
+    });
+}
@@ -0,0 +1,164 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::cell::RefCell;
+use std::env;
+use std::rc::Rc;
+
+use gio::{ApplicationExt, ApplicationFlags, File};
+use gtk::{self, WidgetExt, WindowExt};
+use relm_core::{Core, Remote};
+
+use component::Component;
+use super::{DisplayVariant, create_widget, init_component};
+use widget::Widget;
+use windows::{root_window, set_application, track};
+
+/// Trait to implement for a relm `Widget` started by [`run_application()`](fn.run_application.html).
+///
+/// The methods of this trait convert the signals of the `gtk::Application` into messages sent to
+/// the root widget.
+pub trait ApplicationWidget: Widget {
+    /// Create the message sent when the application is asked to open `files`.
+    ///
+    /// ## Note
+    /// This is only called when the `APPLICATION_HANDLES_OPEN` flag is used.
+    fn open(_files: Vec<File>, _hint: &str) -> Option<Self::Msg> {
+        None
+    }
+
+    /// Create the message sent when the application receives a command line.
+    /// The first argument is the name of the program.
+    ///
+    /// ## Note
+    /// This is only called when the `APPLICATION_HANDLES_COMMAND_LINE` flag is used.
+    fn command_line(_arguments: Vec<String>) -> Option<Self::Msg> {
+        None
+    }
+}
+
+struct AppState<WIDGET: Widget>
+    where WIDGET::Model: Clone,
+{
+    component: Option<Component<WIDGET>>,
+    model_param: Option<WIDGET::ModelParam>,
+    remote: Remote,
+}
+
+impl<WIDGET> AppState<WIDGET>
+    where WIDGET: Widget + 'static,
+          WIDGET::Model: Clone + Send,
+          WIDGET::Msg: Clone + DisplayVariant + Send + 'static,
+{
+    /// Create the root component the first time the application is activated and present its
+    /// window on every activation.
+    fn activate(&mut self) {
+        if self.component.is_none() {
+            if let Some(model_param) = self.model_param.take() {
+                let component = create_widget::<WIDGET>(&self.remote, model_param);
+                if let Some(window) = root_window(&component.widget) {
+                    let _ = track(&window, None);
+                }
+                init_component::<WIDGET>(&component, &self.remote);
+                self.component = Some(Component::new(component));
+            }
+        }
+        if let Some(ref component) = self.component {
+            if let Some(window) = root_window(component.widget()) {
+                window.present();
+            }
+            else {
+                component.widget().root().show();
+            }
+        }
+    }
+
+    fn emit(&self, msg: Option<WIDGET::Msg>) {
+        if let (Some(msg), Some(component)) = (msg, self.component.as_ref()) {
+            component.stream().emit(msg);
+        }
+    }
+}
+
+/// Create the specified relm `Widget` inside a `gtk::Application` and run it.
+///
+/// The root widget is created when the application is activated.
+/// If the root of the `Widget` is a `gtk::Window`, it is added to the application, which quits
+/// when its last window is closed.
+/// The windows opened by [`open_window()`](fn.open_window.html) are added to the application too.
+/// The `open` and `command-line` signals are converted to messages by the methods of the
+/// [`ApplicationWidget`](trait.ApplicationWidget.html) trait.
+///
+/// ## Warning
+/// The application does not run in `gtk::main()`, so `gtk::main_quit()` will not stop it: close
+/// the window or call `quit()` on the `gtk::Application` instead.
+///
+/// Returns the exit status of the application.
+pub fn run_application<WIDGET>(application_id: &str, flags: ApplicationFlags, model_param: WIDGET::ModelParam)
+        -> Result<i32, ()>
+    where WIDGET: ApplicationWidget + 'static,
+          WIDGET::Model: Clone + Send,
+          WIDGET::Msg: Send,
+{
+    gtk::init()?;
+    let application = gtk::Application::new(Some(application_id), flags).map_err(|_| ())?;
+    set_application(&application);
+
+    let state = Rc::new(RefCell::new(AppState::<WIDGET> {
+        component: None,
+        model_param: Some(model_param),
+        remote: Core::run(),
+    }));
+
+    {
+        let state = state.clone();
+        let _ = application.connect_activate(move |_| {
+            state.borrow_mut().activate();
+        });
+    }
+
+    {
+        let state = state.clone();
+        let _ = application.connect_open(move |_, files, hint| {
+            // The activate signal is not emitted when files are opened.
+            state.borrow_mut().activate();
+            state.borrow().emit(WIDGET::open(files.to_vec(), hint));
+        });
+    }
+
+    {
+        let state = state.clone();
+        let _ = application.connect_command_line(move |_, command_line| {
+            // The activate signal is not emitted when the application handles the command line.
+            state.borrow_mut().activate();
+            let arguments = command_line.get_arguments().into_iter()
+                .map(|argument| argument.to_string_lossy().into_owned())
+                .collect();
+            state.borrow().emit(WIDGET::command_line(arguments));
+            0
+        });
+    }
+
+    let arguments: Vec<String> = env::args().collect();
+    let status = application.run(&arguments);
+    let _component = state.borrow_mut().component.take();
+    Ok(status)
+}
@@ -19,12 +19,26 @@
  * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
+use std::cell::RefCell;
+use std::mem;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
+use gtk::{self, WidgetExt};
+
 use super::{EventStream, Receiver, Widget};
 
+/// A relm widget added to a relm container with `RelmContainer::add_widget()`.
+pub struct Child<WIDGET> {
+    // Remove the child with Container::remove_widget().
+    pub remove: Box<Fn(&WIDGET)>,
+    pub root: gtk::Widget,
+    pub stop: Box<Fn()>,
+}
+
 #[derive(Clone)]
 pub struct Comp<WIDGET: Widget> {
+    pub children: Rc<RefCell<Vec<Child<WIDGET>>>>,
     pub model: Arc<Mutex<WIDGET::Model>>,
     pub _receiver: Arc<Receiver>,
     pub stream: EventStream<WIDGET::Msg>,
@@ -72,4 +86,24 @@ impl<WIDGET: Widget> Component<WIDGET>
     pub fn widget(&self) -> &WIDGET {
         &self.0.widget
     }
+
+    /// Get the relm widgets added to this component with `RelmContainer::add_widget()`.
+    /// This is used internally by the library.
+    #[doc(hidden)]
+    pub fn children(&self) -> &RefCell<Vec<Child<WIDGET>>> {
+        &self.0.children
+    }
+
+    /// Stop the widget after it is removed from its container: its stream is closed, which cancels
+    /// its futures, its relm children are stopped and its root widget is destroyed.
+    /// This is used internally by the library.
+    #[doc(hidden)]
+    pub fn stop(&self) {
+        let _ = self.0.stream.close();
+        let children = mem::replace(&mut *self.0.children.borrow_mut(), vec![]);
+        for child in children {
+            (child.stop)();
+        }
+        self.0.widget.root().destroy();
+    }
 }
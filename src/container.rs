@@ -19,14 +19,100 @@
  * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
+use std::mem;
+
 use glib::Cast;
+use glib::object::Downcast;
 use gtk;
-use gtk::{ContainerExt, IsA, Object, WidgetExt};
+use gtk::{BoxExt, ContainerExt, IsA, Object, WidgetExt};
 
-use component::Component;
+use component::{Child, Component};
+use gtk_ext::GridExtManual;
 use super::{DisplayVariant, RemoteRelm, create_widget, init_component};
 use widget::Widget;
 
+/// Where a relm widget is added in a container, as returned by
+/// [`Widget::placement()`](trait.Widget.html#method.placement).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Placement {
+    /// The id of the containing widget, like the one returned by `Widget::parent_id()`.
+    pub parent_id: Option<String>,
+    /// The position of the widget in its container: it is added at the end when this is `None`.
+    pub position: Option<Position>,
+}
+
+/// The position of a widget in its container.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Position {
+    /// The cell of a `gtk::Grid` where the widget is attached, spanning `width` columns and `height`
+    /// rows.
+    Cell {
+        left: i32,
+        top: i32,
+        width: i32,
+        height: i32,
+    },
+    /// The index of the widget in a `gtk::Box` or a `gtk::Notebook`.
+    Index(i32),
+}
+
+/// Add `child` to `container` at `position`, or at the end when it is `None`.
+///
+/// This is useful for custom implementations of
+/// [`Container::add_widget_at()`](trait.Container.html#method.add_widget_at).
+pub fn add_child<C, W>(container: &C, child: &W, position: Option<Position>)
+    where C: Clone + IsA<gtk::Container> + IsA<Object>,
+          W: IsA<gtk::Widget>,
+{
+    let container: gtk::Container = container.clone().upcast();
+    let container =
+        match (position, container.downcast::<gtk::Grid>()) {
+            (Some(Position::Cell { left, top, width, height }), Ok(grid)) => {
+                grid.attach(child, left, top, width, height);
+                return;
+            },
+            (_, Ok(grid)) => grid.upcast(),
+            (_, Err(container)) => container,
+        };
+    container.add(child);
+    if let Some(position) = position {
+        move_child(child, position);
+    }
+}
+
+/// Move `child` to `position` in its parent.
+/// The position is ignored when it does not apply to the parent, like an index in a `gtk::Grid`.
+pub fn move_child<W: IsA<gtk::Widget>>(child: &W, position: Position) {
+    let parent =
+        match child.get_parent() {
+            Some(parent) => parent,
+            None => return,
+        };
+    match position {
+        Position::Cell { left, top, width, height } => {
+            if let Ok(grid) = parent.downcast::<gtk::Grid>() {
+                grid.set_child_left_attach(child, left);
+                grid.set_child_top_attach(child, top);
+                grid.set_child_width(child, width);
+                grid.set_child_height(child, height);
+            }
+        },
+        Position::Index(index) => {
+            let parent =
+                match parent.downcast::<gtk::Box>() {
+                    Ok(gtk_box) => {
+                        gtk_box.reorder_child(child, index);
+                        return;
+                    },
+                    Err(parent) => parent,
+                };
+            if let Ok(notebook) = parent.downcast::<gtk::Notebook>() {
+                notebook.reorder_child(child, index);
+            }
+        },
+    }
+}
+
 /// Trait to implement relm container widget.
 pub trait Container: Widget {
     /// The type of the containing widget, i.e. where the child widgets will be added.
@@ -46,6 +132,41 @@ pub trait Container: Widget {
         container.add(widget.root());
         container.clone().upcast()
     }
+
+    /// Add a relm widget to this container at `placement`.
+    ///
+    /// The placement is computed from the model parameter of the widget by
+    /// [`Widget::placement()`](trait.Widget.html#method.placement): a custom implementation can
+    /// choose the containing widget from its `parent_id` and use [`add_child()`](fn.add_child.html) to
+    /// put the widget at its `position`.
+    /// By default, the widget is added with [`add_widget()`](#method.add_widget) and then moved to
+    /// its `position`.
+    /// The containing widget is returned.
+    fn add_widget_at<WIDGET: Widget>(&self, widget: &WIDGET, placement: &Placement) -> gtk::Container {
+        let container = self.add_widget(widget);
+        if let Some(position) = placement.position {
+            move_child(widget.root(), position);
+        }
+        container
+    }
+
+    /// Remove a relm widget from this container.
+    ///
+    /// The widget is removed from the widget containing it, so a custom implementation only needs
+    /// to override this method to update its own state.
+    fn remove_widget<WIDGET: Widget>(&self, widget: &WIDGET) {
+        let root = widget.root();
+        if let Some(parent) = root.get_parent() {
+            if let Ok(container) = parent.downcast::<gtk::Container>() {
+                container.remove(root);
+            }
+        }
+    }
+
+    /// Move a relm widget of this container to `position`.
+    fn reorder<WIDGET: Widget>(&self, widget: &WIDGET, position: Position) {
+        move_child(widget.root(), position);
+    }
 }
 
 /// Extension trait for GTK+ containers to add and remove relm `Widget`s.
@@ -64,13 +185,14 @@ pub trait ContainerWidget {
               CHILDWIDGET::Root: IsA<gtk::Widget> + IsA<Object> + WidgetExt;
 
     /// Remove a relm `Widget` from the current GTK+ container.
+    /// Its stream is closed, its futures are cancelled and its root widget is destroyed.
     fn remove_widget<CHILDWIDGET>(&self, component: Component<CHILDWIDGET>)
         where CHILDWIDGET: Widget,
               CHILDWIDGET::Model: Clone,
               CHILDWIDGET::Root: IsA<gtk::Widget>;
 }
 
-impl<W: Clone + ContainerExt + IsA<gtk::Widget> + IsA<Object>> ContainerWidget for W {
+impl<W: Clone + ContainerExt + IsA<gtk::Container> + IsA<gtk::Widget> + IsA<Object>> ContainerWidget for W {
     fn add_widget<CHILDWIDGET, WIDGET: Widget>(&self, relm: &RemoteRelm<WIDGET>,
             model_param: CHILDWIDGET::ModelParam) -> Component<CHILDWIDGET>
         where CHILDWIDGET: Widget + 'static,
@@ -78,8 +200,9 @@ impl<W: Clone + ContainerExt + IsA<gtk::Widget> + IsA<Object>> ContainerWidget f
               CHILDWIDGET::Msg: Clone + DisplayVariant + Send + 'static,
               CHILDWIDGET::Root: IsA<gtk::Widget> + IsA<Object> + WidgetExt,
     {
+        let placement = CHILDWIDGET::placement(&model_param);
         let component = create_widget::<CHILDWIDGET>(&relm.remote, model_param);
-        self.add(component.widget.root());
+        add_child(self, component.widget.root(), placement.position);
         component.widget.on_add(self.clone());
         init_component::<CHILDWIDGET>(&component, &relm.remote);
         Component::new(component)
@@ -91,6 +214,7 @@ impl<W: Clone + ContainerExt + IsA<gtk::Widget> + IsA<Object>> ContainerWidget f
               WIDGET::Root: IsA<gtk::Widget>,
     {
         self.remove(component.widget().root());
+        component.stop();
     }
 }
 
@@ -107,11 +231,27 @@ pub trait RelmContainer {
               CHILDWIDGET::Msg: Send,
               WIDGET: Widget;
 
-    // TODO: add delete methods?
+    /// Remove all the relm widgets added with [`add_widget()`](#tymethod.add_widget), like with
+    /// [`remove_widget()`](#tymethod.remove_widget).
+    /// The children declared in the `view!` of the container are kept.
+    fn clear(&self);
+
+    /// Move a relm widget of a relm container to `index`, with
+    /// [`Container::reorder()`](trait.Container.html#method.reorder).
+    fn move_widget<CHILDWIDGET>(&self, component: &Component<CHILDWIDGET>, index: i32)
+        where CHILDWIDGET: Widget,
+              CHILDWIDGET::Model: Clone;
+
+    /// Remove a relm widget from a relm container, with
+    /// [`Container::remove_widget()`](trait.Container.html#method.remove_widget).
+    /// Its stream is closed, its futures are cancelled and its root widget is destroyed.
+    fn remove_widget<CHILDWIDGET>(&self, component: Component<CHILDWIDGET>)
+        where CHILDWIDGET: Widget,
+              CHILDWIDGET::Model: Clone;
 }
 
 impl<WIDGET> RelmContainer for Component<WIDGET>
-    where WIDGET: Container + Widget,
+    where WIDGET: Container + Widget + 'static,
           WIDGET::Container: Clone + ContainerExt + IsA<gtk::Widget> + IsA<Object>,
           WIDGET::Model: Clone,
 {
@@ -126,10 +266,44 @@ impl<WIDGET> RelmContainer for Component<WIDGET>
               CHILDWIDGET::Msg: Send,
               PARENTWIDGET: Widget
     {
+        let placement = CHILDWIDGET::placement(&model_param);
         let component = create_widget::<CHILDWIDGET>(&relm.remote, model_param);
-        let container = self.widget().add_widget(&component.widget);
+        let container = self.widget().add_widget_at(&component.widget, &placement);
         component.widget.on_add(container.clone());
         init_component::<CHILDWIDGET>(&component, &relm.remote);
-        Component::new(component)
+        let component = Component::new(component);
+        let removed = component.clone();
+        let stopped = component.clone();
+        self.children().borrow_mut().push(Child {
+            remove: Box::new(move |parent: &WIDGET| parent.remove_widget(removed.widget())),
+            root: component.widget().root().clone().upcast(),
+            stop: Box::new(move || stopped.stop()),
+        });
+        component
+    }
+
+    fn clear(&self) {
+        let children = mem::replace(&mut *self.children().borrow_mut(), vec![]);
+        for child in children {
+            (child.remove)(self.widget());
+            (child.stop)();
+        }
+    }
+
+    fn move_widget<CHILDWIDGET>(&self, component: &Component<CHILDWIDGET>, index: i32)
+        where CHILDWIDGET: Widget,
+              CHILDWIDGET::Model: Clone,
+    {
+        self.widget().reorder(component.widget(), Position::Index(index));
+    }
+
+    fn remove_widget<CHILDWIDGET>(&self, component: Component<CHILDWIDGET>)
+        where CHILDWIDGET: Widget,
+              CHILDWIDGET::Model: Clone,
+    {
+        let root: gtk::Widget = component.widget().root().clone().upcast();
+        self.children().borrow_mut().retain(|child| child.root != root);
+        self.widget().remove_widget(component.widget());
+        component.stop();
     }
 }
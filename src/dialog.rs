@@ -0,0 +1,173 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures::sync::oneshot;
+use glib::{self, Continue};
+use gtk::{self, WidgetExt, WindowExt};
+
+use component::Component;
+use super::{DisplayVariant, RemoteRelm, UpdateHook, create_widget_with_hook, init_component};
+use widget::Widget;
+use windows::toplevel_window;
+
+/// Trait to implement for a relm `Widget` shown by [`dialog()`](fn.dialog.html) or
+/// [`dialog_future()`](fn.dialog_future.html).
+pub trait DialogWidget: Widget {
+    /// The type of the value produced when the user responds to the dialog.
+    type Response;
+
+    /// Get the response of the dialog after `msg` was processed by `update()`.
+    /// Returning `Some(response)` closes the dialog.
+    fn response(&self, msg: Self::Msg, model: &Self::Model) -> Option<Self::Response>;
+
+    /// Get the response of the dialog when it is closed by the user without responding, for
+    /// instance with the close button of the window.
+    fn close_response() -> Option<Self::Response> {
+        None
+    }
+}
+
+type SharedComponent<DIALOG> = Rc<RefCell<Option<Component<DIALOG>>>>;
+
+/*
+ * Show the DIALOG in a modal window and call respond() once, either with the response or with the
+ * close response when the window is closed.
+ */
+fn present<DIALOG, WIDGET, RESPOND>(relm: &RemoteRelm<WIDGET>, parent: Option<&gtk::Window>,
+        model_param: DIALOG::ModelParam, respond: RESPOND)
+    where DIALOG: DialogWidget + 'static,
+          DIALOG::Model: Clone + Send,
+          DIALOG::Msg: Clone + DisplayVariant + Send + 'static,
+          WIDGET: Widget,
+          RESPOND: FnOnce(Option<DIALOG::Response>) + 'static,
+{
+    let respond = Rc::new(RefCell::new(Some(respond)));
+    let component: SharedComponent<DIALOG> = Rc::new(RefCell::new(None));
+
+    let hook: UpdateHook<DIALOG> = {
+        let respond = respond.clone();
+        let component = component.clone();
+        Box::new(move |dialog: &DIALOG, msg, model: &DIALOG::Model| {
+            if let Some(response) = dialog.response(msg, model) {
+                if let Some(respond) = respond.borrow_mut().take() {
+                    respond(Some(response));
+                }
+                close(&component);
+            }
+        })
+    };
+    let dialog = create_widget_with_hook::<DIALOG>(&relm.remote, model_param, Some(hook));
+    let window = toplevel_window(&dialog.widget);
+    window.set_modal(true);
+    window.set_transient_for(parent);
+    {
+        let component = component.clone();
+        let _ = window.connect_destroy(move |_| {
+            if let Some(respond) = respond.borrow_mut().take() {
+                respond(DIALOG::close_response());
+            }
+            close(&component);
+        });
+    }
+    init_component::<DIALOG>(&dialog, &relm.remote);
+    *component.borrow_mut() = Some(Component::new(dialog));
+    window.present();
+}
+
+fn close<DIALOG>(component: &SharedComponent<DIALOG>)
+    where DIALOG: Widget + 'static,
+          DIALOG::Model: Clone,
+{
+    // This is called from the update of the dialog, so it is destroyed once the update is done.
+    let mut component = component.borrow_mut().take();
+    let _ = glib::idle_add(move || {
+        if let Some(component) = component.take() {
+            if let Some(window) = component.widget().root().get_toplevel() {
+                window.destroy();
+            }
+        }
+        Continue(false)
+    });
+}
+
+/// Show a relm widget in a modal dialog without blocking in a nested main loop.
+///
+/// When the dialog responds (see [`DialogWidget::response()`](trait.DialogWidget.html#tymethod.response)),
+/// it is closed and the message returned by `callback` is sent to the widget that opened it.
+/// `callback` can return an `Option` when no message needs to be sent.
+/// If the root of the dialog is not a `gtk::Window`, it is added to a new one.
+///
+/// ```ignore
+/// fn update(&mut self, event: Msg, _model: &mut ()) {
+///     match event {
+///         ConfirmQuit => relm::dialog::<Confirm, _, _, _>(&self.relm, Some(&self.window), (),
+///             |quit| if quit { Some(Quit) } else { None }),
+///         Quit => gtk::main_quit(),
+///     }
+/// }
+/// ```
+pub fn dialog<DIALOG, WIDGET, CALLBACK, MSG>(relm: &RemoteRelm<WIDGET>, parent: Option<&gtk::Window>,
+        model_param: DIALOG::ModelParam, callback: CALLBACK)
+    where DIALOG: DialogWidget + 'static,
+          DIALOG::Model: Clone + Send,
+          DIALOG::Msg: Clone + DisplayVariant + Send + 'static,
+          WIDGET: Widget,
+          WIDGET::Msg: 'static,
+          CALLBACK: FnOnce(DIALOG::Response) -> MSG + 'static,
+          MSG: Into<Option<WIDGET::Msg>>,
+{
+    let stream = relm.stream().clone();
+    present::<DIALOG, _, _>(relm, parent, model_param, move |response| {
+        if let Some(response) = response {
+            let msg: Option<_> = callback(response).into();
+            if let Some(msg) = msg {
+                stream.emit(msg);
+            }
+        }
+    });
+}
+
+/// Show a relm widget in a modal dialog and get a `Future` resolving to its response.
+///
+/// The `Future` fails with `Canceled` if the dialog is closed without a response.
+///
+/// ## Note
+/// This function must be called in the GTK+ thread, but the `Future` can be sent to the tokio
+/// thread.
+pub fn dialog_future<DIALOG, WIDGET>(relm: &RemoteRelm<WIDGET>, parent: Option<&gtk::Window>,
+        model_param: DIALOG::ModelParam) -> oneshot::Receiver<DIALOG::Response>
+    where DIALOG: DialogWidget + 'static,
+          DIALOG::Model: Clone + Send,
+          DIALOG::Msg: Clone + DisplayVariant + Send + 'static,
+          DIALOG::Response: 'static,
+          WIDGET: Widget,
+{
+    let (sender, receiver) = oneshot::channel();
+    present::<DIALOG, _, _>(relm, parent, model_param, move |response| {
+        if let Some(response) = response {
+            let _ = sender.send(response);
+        }
+    });
+    receiver
+}
@@ -19,13 +19,49 @@
  * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
-//! This module contains methods to set the child properties of a `gtk::Box`.
+//! This module contains methods to set the child properties of the GTK+ containers and the action
+//! of a `gtk::Actionable`, a function to create a widget with its construct properties, a
+//! function to connect a signal by its name and functions to create the views of the Glade files.
 
 // TODO: remove this file when the next gtk-rs version release.
 
-use glib::translate::{ToGlib, ToGlibPtr};
+use std::ffi::CString;
+use std::mem;
+use std::os::raw::{c_uint, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use std::ptr;
+
+use glib::{Object, Type};
+use glib::object::Downcast;
+use glib::translate::{FromGlibPtrNone, ToGlib, ToGlibPtr, from_glib_none};
+use gobject_sys;
 use gtk;
-use gtk::{IsA, PackType, Value, Widget};
+use gtk::{
+    Actionable,
+    Align,
+    ArrowType,
+    Button,
+    ButtonBoxStyle,
+    ButtonExt,
+    Container,
+    Dialog,
+    DialogExt,
+    IsA,
+    Justification,
+    Orientation,
+    PackType,
+    PolicyType,
+    PositionType,
+    ReliefStyle,
+    SelectionMode,
+    ShadowType,
+    Value,
+    Widget,
+    WidgetExt,
+    WindowPosition,
+    WindowType,
+};
 use gtk_sys;
 
 /// A trait providing methods to set the child properties of a `gtk::Box`.
@@ -48,32 +84,493 @@ pub trait BoxExtManual {
 
 impl BoxExtManual for gtk::Box {
     fn set_child_expand<T: IsA<Widget>>(&self, child: &T, expand: bool) {
-        let expand = Value::from(&expand);
-        unsafe { gtk_sys::gtk_container_child_set_property(self.to_glib_none().0, child.to_glib_none().0,
-            "expand".to_glib_none().0, expand.to_glib_none().0) }
+        set_child_property(self, child, "expand", &expand);
     }
 
     fn set_child_fill<T: IsA<Widget>>(&self, child: &T, fill: bool) {
-        let fill = Value::from(&fill);
-        unsafe { gtk_sys::gtk_container_child_set_property(self.to_glib_none().0, child.to_glib_none().0,
-            "fill".to_glib_none().0, fill.to_glib_none().0) }
+        set_child_property(self, child, "fill", &fill);
     }
 
     fn set_child_pack_type<T: IsA<Widget>>(&self, child: &T, pack_type: PackType) {
-        let pack_type = Value::from(&(pack_type.to_glib() as i32));
-        unsafe { gtk_sys::gtk_container_child_set_property(self.to_glib_none().0, child.to_glib_none().0,
-            "pack-type".to_glib_none().0, pack_type.to_glib_none().0) }
+        set_child_property(self, child, "pack-type", &pack_type);
     }
 
     fn set_child_padding<T: IsA<Widget>>(&self, child: &T, padding: u32) {
-        let padding = Value::from(&padding);
-        unsafe { gtk_sys::gtk_container_child_set_property(self.to_glib_none().0, child.to_glib_none().0,
-            "padding".to_glib_none().0, padding.to_glib_none().0) }
+        set_child_property(self, child, "padding", &padding);
     }
 
     fn set_child_position<T: IsA<Widget>>(&self, child: &T, position: i32) {
-        let position = Value::from(&position);
-        unsafe { gtk_sys::gtk_container_child_set_property(self.to_glib_none().0, child.to_glib_none().0,
-            "position".to_glib_none().0, position.to_glib_none().0) }
+        set_child_property(self, child, "position", &position);
+    }
+}
+
+/// A trait providing methods to set the child properties of a `gtk::Fixed`.
+pub trait FixedExtManual {
+    /// Set the horizontal position of the child, in pixels.
+    fn set_child_x<T: IsA<Widget>>(&self, child: &T, x: i32);
+
+    /// Set the vertical position of the child, in pixels.
+    fn set_child_y<T: IsA<Widget>>(&self, child: &T, y: i32);
+}
+
+impl FixedExtManual for gtk::Fixed {
+    fn set_child_x<T: IsA<Widget>>(&self, child: &T, x: i32) {
+        set_child_property(self, child, "x", &x);
+    }
+
+    fn set_child_y<T: IsA<Widget>>(&self, child: &T, y: i32) {
+        set_child_property(self, child, "y", &y);
+    }
+}
+
+/// A trait providing methods to set the child properties of a `gtk::Grid`.
+pub trait GridExtManual {
+    /// Set the number of rows that the child spans.
+    fn set_child_height<T: IsA<Widget>>(&self, child: &T, height: i32);
+
+    /// Set the column number to attach the left side of the child to.
+    fn set_child_left_attach<T: IsA<Widget>>(&self, child: &T, left_attach: i32);
+
+    /// Set the row number to attach the top side of the child to.
+    fn set_child_top_attach<T: IsA<Widget>>(&self, child: &T, top_attach: i32);
+
+    /// Set the number of columns that the child spans.
+    fn set_child_width<T: IsA<Widget>>(&self, child: &T, width: i32);
+}
+
+impl GridExtManual for gtk::Grid {
+    fn set_child_height<T: IsA<Widget>>(&self, child: &T, height: i32) {
+        set_child_property(self, child, "height", &height);
+    }
+
+    fn set_child_left_attach<T: IsA<Widget>>(&self, child: &T, left_attach: i32) {
+        set_child_property(self, child, "left-attach", &left_attach);
+    }
+
+    fn set_child_top_attach<T: IsA<Widget>>(&self, child: &T, top_attach: i32) {
+        set_child_property(self, child, "top-attach", &top_attach);
+    }
+
+    fn set_child_width<T: IsA<Widget>>(&self, child: &T, width: i32) {
+        set_child_property(self, child, "width", &width);
+    }
+}
+
+/// A trait providing methods to set the child properties of a `gtk::Notebook`.
+pub trait NotebookExtManual {
+    /// Set whether the tab of the child can be detached.
+    fn set_child_detachable<T: IsA<Widget>>(&self, child: &T, detachable: bool);
+
+    /// Set the text of the menu entry of the child.
+    fn set_child_menu_label<T: IsA<Widget>>(&self, child: &T, menu_label: &str);
+
+    /// Set the index of the child in the parent.
+    fn set_child_position<T: IsA<Widget>>(&self, child: &T, position: i32);
+
+    /// Set whether the tab of the child can be reordered by the user.
+    fn set_child_reorderable<T: IsA<Widget>>(&self, child: &T, reorderable: bool);
+
+    /// Set whether the tab of the child should receive extra space when the parent grows.
+    fn set_child_tab_expand<T: IsA<Widget>>(&self, child: &T, tab_expand: bool);
+
+    /// Set whether the tab of the child should fill the space allocated to it.
+    fn set_child_tab_fill<T: IsA<Widget>>(&self, child: &T, tab_fill: bool);
+
+    /// Set the text of the tab of the child.
+    fn set_child_tab_label<T: IsA<Widget>>(&self, child: &T, tab_label: &str);
+}
+
+impl NotebookExtManual for gtk::Notebook {
+    fn set_child_detachable<T: IsA<Widget>>(&self, child: &T, detachable: bool) {
+        set_child_property(self, child, "detachable", &detachable);
+    }
+
+    fn set_child_menu_label<T: IsA<Widget>>(&self, child: &T, menu_label: &str) {
+        set_child_property(self, child, "menu-label", &menu_label);
+    }
+
+    fn set_child_position<T: IsA<Widget>>(&self, child: &T, position: i32) {
+        set_child_property(self, child, "position", &position);
+    }
+
+    fn set_child_reorderable<T: IsA<Widget>>(&self, child: &T, reorderable: bool) {
+        set_child_property(self, child, "reorderable", &reorderable);
+    }
+
+    fn set_child_tab_expand<T: IsA<Widget>>(&self, child: &T, tab_expand: bool) {
+        set_child_property(self, child, "tab-expand", &tab_expand);
+    }
+
+    fn set_child_tab_fill<T: IsA<Widget>>(&self, child: &T, tab_fill: bool) {
+        set_child_property(self, child, "tab-fill", &tab_fill);
+    }
+
+    fn set_child_tab_label<T: IsA<Widget>>(&self, child: &T, tab_label: &str) {
+        set_child_property(self, child, "tab-label", &tab_label);
+    }
+}
+
+/// A trait providing methods to set the child properties of a `gtk::Overlay`.
+pub trait OverlayExtManual {
+    /// Set the index of the child in the parent.
+    fn set_child_index<T: IsA<Widget>>(&self, child: &T, index: i32);
+
+    /// Set whether the input events go through the child.
+    fn set_child_pass_through<T: IsA<Widget>>(&self, child: &T, pass_through: bool);
+}
+
+impl OverlayExtManual for gtk::Overlay {
+    fn set_child_index<T: IsA<Widget>>(&self, child: &T, index: i32) {
+        set_child_property(self, child, "index", &index);
+    }
+
+    fn set_child_pass_through<T: IsA<Widget>>(&self, child: &T, pass_through: bool) {
+        set_child_property(self, child, "pass-through", &pass_through);
+    }
+}
+
+/// A trait providing methods to set the child properties of a `gtk::Paned`.
+pub trait PanedExtManual {
+    /// Set whether the child is resized when the parent is resized.
+    fn set_child_resize<T: IsA<Widget>>(&self, child: &T, resize: bool);
+
+    /// Set whether the child can be made smaller than its requisition.
+    fn set_child_shrink<T: IsA<Widget>>(&self, child: &T, shrink: bool);
+}
+
+impl PanedExtManual for gtk::Paned {
+    fn set_child_resize<T: IsA<Widget>>(&self, child: &T, resize: bool) {
+        set_child_property(self, child, "resize", &resize);
+    }
+
+    fn set_child_shrink<T: IsA<Widget>>(&self, child: &T, shrink: bool) {
+        set_child_property(self, child, "shrink", &shrink);
+    }
+}
+
+#[cfg(feature = "v3_10")]
+/// A trait providing methods to set the child properties of a `gtk::Stack`.
+pub trait StackExtManual {
+    /// Set the name of the icon of the child.
+    fn set_child_icon_name<T: IsA<Widget>>(&self, child: &T, icon_name: &str);
+
+    /// Set the name of the child, used to make it visible.
+    fn set_child_name<T: IsA<Widget>>(&self, child: &T, name: &str);
+
+    /// Set whether the child requires the attention of the user.
+    fn set_child_needs_attention<T: IsA<Widget>>(&self, child: &T, needs_attention: bool);
+
+    /// Set the index of the child in the parent.
+    fn set_child_position<T: IsA<Widget>>(&self, child: &T, position: i32);
+
+    /// Set the title of the child.
+    fn set_child_title<T: IsA<Widget>>(&self, child: &T, title: &str);
+}
+
+#[cfg(feature = "v3_10")]
+impl StackExtManual for gtk::Stack {
+    fn set_child_icon_name<T: IsA<Widget>>(&self, child: &T, icon_name: &str) {
+        set_child_property(self, child, "icon-name", &icon_name);
+    }
+
+    fn set_child_name<T: IsA<Widget>>(&self, child: &T, name: &str) {
+        set_child_property(self, child, "name", &name);
+    }
+
+    fn set_child_needs_attention<T: IsA<Widget>>(&self, child: &T, needs_attention: bool) {
+        set_child_property(self, child, "needs-attention", &needs_attention);
+    }
+
+    fn set_child_position<T: IsA<Widget>>(&self, child: &T, position: i32) {
+        set_child_property(self, child, "position", &position);
+    }
+
+    fn set_child_title<T: IsA<Widget>>(&self, child: &T, title: &str) {
+        set_child_property(self, child, "title", &title);
+    }
+}
+
+/// A trait providing a method to set the action of a `gtk::Actionable` (buttons, menu items…).
+pub trait ActionableExtManual {
+    /// Set the detailed name of the action activated by this widget, like `"win.save"`.
+    fn set_action(&self, detailed_action_name: &str);
+}
+
+impl<T: IsA<Actionable>> ActionableExtManual for T {
+    fn set_action(&self, detailed_action_name: &str) {
+        unsafe { gtk_sys::gtk_actionable_set_detailed_action_name(self.to_glib_none().0,
+            detailed_action_name.to_glib_none().0) }
+    }
+}
+
+/// A value which can be given to `set_child_property()` or `new_widget()`.
+/// It is converted to the type of the property when it is set.
+pub trait PropertyValue {
+    /// Convert the value to a `gtk::Value`.
+    fn to_property_value(&self) -> Value;
+}
+
+macro_rules! impl_property_value {
+    ($($typ:ty),*) => {
+        $(
+            impl PropertyValue for $typ {
+                fn to_property_value(&self) -> Value {
+                    Value::from(self)
+                }
+            }
+        )*
+    };
+}
+
+// The enums are converted to an integer, which GLib transforms to the enum type of the property.
+macro_rules! impl_enum_property_value {
+    ($($typ:ty),*) => {
+        $(
+            impl PropertyValue for $typ {
+                fn to_property_value(&self) -> Value {
+                    Value::from(&(self.to_glib() as i32))
+                }
+            }
+        )*
+    };
+}
+
+impl_property_value!(bool, f32, f64, i32, i64, u32, u64, str);
+
+impl_enum_property_value!(Align, ArrowType, ButtonBoxStyle, Justification, Orientation, PackType, PolicyType,
+    PositionType, ReliefStyle, SelectionMode, ShadowType, WindowPosition, WindowType);
+
+impl PropertyValue for String {
+    fn to_property_value(&self) -> Value {
+        Value::from(self.as_str())
+    }
+}
+
+impl<'a, T: PropertyValue + ?Sized> PropertyValue for &'a T {
+    fn to_property_value(&self) -> Value {
+        (**self).to_property_value()
+    }
+}
+
+/// Create a widget of type `typ` with its construct properties, like `("spacing", &6)`.
+/// An error is returned when the type has no such property or when a value cannot be converted to
+/// the type of its property.
+/// This is used by the `#[widget]` attribute for `gtk::Box(orientation = Vertical, spacing = 6)`.
+pub fn new_widget(typ: Type, properties: &[(&str, &PropertyValue)]) -> Result<Widget, String> {
+    unsafe {
+        let typ = typ.to_glib();
+        let class = gobject_sys::g_type_class_ref(typ) as *mut gobject_sys::GObjectClass;
+        let names: Vec<_> = properties.iter()
+            .map(|&(name, _)| CString::new(name).expect("property name"))
+            .collect();
+        let mut parameters = vec![];
+        let mut error = None;
+        for (name, &(_, value)) in names.iter().zip(properties) {
+            let pspec = gobject_sys::g_object_class_find_property(class, name.as_ptr());
+            if pspec.is_null() {
+                error = Some(format!("Unknown construct property {:?}", name));
+                break;
+            }
+            let source = value.to_property_value();
+            let mut parameter: gobject_sys::GParameter = mem::zeroed();
+            parameter.name = name.as_ptr();
+            gobject_sys::g_value_init(&mut parameter.value, (*pspec).value_type);
+            parameters.push(parameter);
+            let last = parameters.len() - 1;
+            if gobject_sys::g_value_transform(source.to_glib_none().0, &mut parameters[last].value) == 0 {
+                error = Some(format!("Wrong type for the construct property {:?}", name));
+                break;
+            }
+        }
+        let result =
+            match error {
+                Some(error) => Err(error),
+                None => {
+                    let object = gobject_sys::g_object_newv(typ, parameters.len() as u32, parameters.as_mut_ptr());
+                    Ok(Widget::from_glib_none(object as *mut _))
+                },
+            };
+        for parameter in &mut parameters {
+            gobject_sys::g_value_unset(&mut parameter.value);
+        }
+        gobject_sys::g_type_class_unref(class as *mut _);
+        result
+    }
+}
+
+/// Log an error returned by a function of this module.
+/// This is used by the code generated by the `#[widget]` attribute, which cannot use the `log` crate.
+#[doc(hidden)]
+pub fn warn_error(error: &str) {
+    warn!("{}", error);
+}
+
+type SignalCallback = Box<Fn(&[Value]) -> Option<Value>>;
+
+/// Connect a callback to a signal from its name, like `"notify::position"`, for the signals which
+/// have no `connect_*()` method.
+/// The callback receives the parameters of the signal, starting with the object, and returns the
+/// value to return from the signal handler, if any.
+/// An error is returned when the object has no such signal.
+/// This is used by the `#[widget]` attribute for `signal "notify::position"(paned, _) => Msg`.
+pub fn connect_signal<O, F>(object: &O, signal_name: &str, callback: F) -> Result<u64, String>
+    where O: IsA<Object>,
+          F: Fn(&[Value]) -> Option<Value> + 'static,
+{
+    let callback: Box<SignalCallback> = Box::new(Box::new(callback));
+    let callback = Box::into_raw(callback) as *mut c_void;
+    let name = CString::new(signal_name).expect("signal name");
+    unsafe {
+        let closure = gobject_sys::g_closure_new_simple(mem::size_of::<gobject_sys::GClosure>() as u32,
+            callback);
+        gobject_sys::g_closure_set_marshal(closure, Some(marshal_signal));
+        gobject_sys::g_closure_add_finalize_notifier(closure, callback, Some(destroy_signal_callback));
+        let handler_id = gobject_sys::g_signal_connect_closure(object.to_glib_none().0 as *mut _, name.as_ptr(),
+            closure, 0);
+        if handler_id == 0 {
+            // The closure was not used by a signal, so sinking it frees the callback.
+            gobject_sys::g_closure_sink(closure);
+            return Err(format!("Unknown signal {:?}", signal_name));
+        }
+        Ok(handler_id as u64)
+    }
+}
+
+unsafe extern "C" fn marshal_signal(closure: *mut gobject_sys::GClosure, return_value: *mut gobject_sys::GValue,
+    n_param_values: c_uint, param_values: *mut gobject_sys::GValue, _invocation_hint: *mut c_void,
+    _marshal_data: *mut c_void)
+{
+    abort_on_panic(|| {
+        let callback = &*((*closure).data as *const SignalCallback);
+        let values: Vec<Value> = (0..n_param_values as isize)
+            .map(|index| from_glib_none(param_values.offset(index) as *const _))
+            .collect();
+        if let Some(value) = callback(&values) {
+            if !return_value.is_null() && gobject_sys::g_value_transform(value.to_glib_none().0, return_value) == 0
+            {
+                error!("Wrong type for the value returned from the signal handler");
+            }
+        }
+    })
+}
+
+unsafe extern "C" fn destroy_signal_callback(callback: *mut c_void, _closure: *mut gobject_sys::GClosure) {
+    abort_on_panic(|| {
+        Box::from_raw(callback as *mut SignalCallback);
+    })
+}
+
+// A panic must not unwind into the C code calling the signal handlers, so the process is aborted
+// after the panic message is printed.
+fn abort_on_panic<F: FnOnce()>(function: F) {
+    if panic::catch_unwind(AssertUnwindSafe(function)).is_err() {
+        error!("Panic in a signal handler, aborting");
+        process::abort();
+    }
+}
+
+/// Set the child property of any container from its name, like `"left-attach"`.
+/// This is used by the `#[widget]` attribute when the type of the parent is only known at runtime.
+pub fn set_child_property<P, C, V>(parent: &P, child: &C, name: &str, value: &V)
+    where P: IsA<Container>,
+          C: IsA<Widget>,
+          V: PropertyValue + ?Sized,
+{
+    let value = value.to_property_value();
+    unsafe { gtk_sys::gtk_container_child_set_property(parent.to_glib_none().0, child.to_glib_none().0,
+        name.to_glib_none().0, value.to_glib_none().0) }
+}
+
+/// Get the child of `widget` created by the widget itself, like the `"action_area"` of a
+/// `gtk::Dialog`.
+/// This is used by the `#[widget]` attribute for the `<child internal-child="name">` of the views
+/// created with Glade.
+pub fn get_internal_child<W: IsA<Object>>(widget: &W, name: &str) -> Result<Widget, String> {
+    let child_name = CString::new(name).expect("internal child name");
+    unsafe {
+        // The builder is only used by the widgets whose internal children come from a template.
+        let builder = gtk_sys::gtk_builder_new();
+        let child = gtk_sys::gtk_buildable_get_internal_child(widget.to_glib_none().0 as *mut _, builder,
+            child_name.as_ptr());
+        gobject_sys::g_object_unref(builder as *mut _);
+        if child.is_null() {
+            return Err(format!("Unknown internal child {:?}", name));
+        }
+        Ok(Widget::from_glib_none(child as *mut _))
+    }
+}
+
+/// Make a button of the action area of a dialog emit the response `response_id` when it is clicked,
+/// like the `<action-widgets>` of a GtkBuilder file.
+/// This is used by the `#[widget]` attribute for the views created with Glade.
+pub fn set_response(button: &Button, response_id: i32) {
+    button.connect_clicked(move |button| {
+        if let Some(toplevel) = button.get_toplevel() {
+            let dialog: Result<Dialog, _> = toplevel.downcast();
+            if let Ok(dialog) = dialog {
+                dialog.response(response_id);
+            }
+        }
+    });
+}
+
+/// Set a property of an object from its value in a GtkBuilder file, like `"center"` for `"halign"`.
+/// The value is converted to the type of the property as GtkBuilder does.
+/// An error is returned when the object has no such property or when the value cannot be converted.
+/// This is used by the `#[widget]` attribute for the views created with Glade.
+pub fn set_property_from_string<O: IsA<Object>>(object: &O, name: &str, value: &str) -> Result<(), String> {
+    let property_name = CString::new(name).expect("property name");
+    unsafe {
+        let object = object.to_glib_none().0;
+        let class = (*(object as *mut gobject_sys::GTypeInstance)).g_class as *mut gobject_sys::GObjectClass;
+        let pspec = gobject_sys::g_object_class_find_property(class, property_name.as_ptr());
+        if pspec.is_null() {
+            return Err(format!("Unknown property {:?}", name));
+        }
+        let mut value = value_from_string(pspec, name, value)?;
+        gobject_sys::g_object_set_property(object, property_name.as_ptr(), &value);
+        gobject_sys::g_value_unset(&mut value);
+    }
+    Ok(())
+}
+
+/// Set a child property from its value in a GtkBuilder file, like `"True"` for `"expand"`.
+/// The value is converted to the type of the child property as GtkBuilder does.
+/// An error is returned when the parent has no such child property or when the value cannot be
+/// converted.
+/// This is used by the `#[widget]` attribute for the views created with Glade.
+pub fn set_child_property_from_string<P, C>(parent: &P, child: &C, name: &str, value: &str) -> Result<(), String>
+    where P: IsA<Container>,
+          C: IsA<Widget>,
+{
+    let property_name = CString::new(name).expect("child property name");
+    unsafe {
+        let parent = parent.to_glib_none().0;
+        let class = (*(parent as *mut gobject_sys::GTypeInstance)).g_class as *mut gobject_sys::GObjectClass;
+        let pspec = gtk_sys::gtk_container_class_find_child_property(class, property_name.as_ptr());
+        if pspec.is_null() {
+            return Err(format!("Unknown child property {:?}", name));
+        }
+        let mut value = value_from_string(pspec, name, value)?;
+        gtk_sys::gtk_container_child_set_property(parent, child.to_glib_none().0, property_name.as_ptr(), &value);
+        gobject_sys::g_value_unset(&mut value);
+    }
+    Ok(())
+}
+
+// The returned value must be unset by the caller.
+unsafe fn value_from_string(pspec: *mut gobject_sys::GParamSpec, name: &str, string: &str)
+    -> Result<gobject_sys::GValue, String>
+{
+    let c_string = CString::new(string)
+        .map_err(|_| format!("Invalid value {:?} for the property {:?}", string, name))?;
+    let builder = gtk_sys::gtk_builder_new();
+    let mut value: gobject_sys::GValue = mem::zeroed();
+    let converted = gtk_sys::gtk_builder_value_from_string(builder, pspec, c_string.as_ptr(), &mut value,
+        ptr::null_mut());
+    gobject_sys::g_object_unref(builder as *mut _);
+    if converted == 0 {
+        return Err(format!("Invalid value {:?} for the property {:?}", string, name));
     }
+    Ok(value)
 }
@@ -81,6 +81,8 @@
  */
 
 extern crate futures;
+extern crate gdk;
+extern crate gio;
 extern crate glib;
 extern crate glib_itc;
 extern crate gobject_sys;
@@ -90,14 +92,25 @@ extern crate gtk_sys;
 extern crate log;
 extern crate relm_core;
 
+mod action;
+mod application;
 mod component;
 mod container;
+mod dialog;
 pub mod gtk_ext;
+mod list;
 mod macros;
+mod menu;
+mod setter;
 mod stream;
 mod widget;
+mod windows;
 
+use std::cell::RefCell;
+use std::mem;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::SystemTime;
 
 use futures::{Future, Stream};
@@ -115,11 +128,20 @@ use relm_core::Core;
 #[doc(hidden)]
 pub use relm_core::{EventStream, Handle, Remote};
 
+pub use action::{Action, ActionGroup, connect_accel};
+pub use application::{ApplicationWidget, run_application};
 use component::Comp;
-pub use container::{Container, ContainerWidget, RelmContainer};
+pub use container::{Container, ContainerWidget, Placement, Position, RelmContainer, add_child};
+pub use dialog::{DialogWidget, dialog, dialog_future};
 pub use component::Component;
+pub use list::{KeyedList, ListItem};
+pub use menu::{attach_menu, attach_popover};
+#[doc(hidden)]
+pub use setter::{ChangedAlways, ChangedValue, LastValue, Setter, SetterValue};
 use stream::ToStream;
 pub use widget::Widget;
+pub use windows::{WindowId, close_window, open_window, window_count, window_stream};
+use windows::{quit_on_last_window, root_window, track};
 
 /// Dummy macro to be used with `#[derive(Widget)]`.
 ///
@@ -162,29 +184,20 @@ macro_rules! relm_connect_ignore {
 }
 
 /// Macro to be used as a stable alternative to the #[widget] attribute.
+///
+/// The widget is generated in the current module, so it supports the same syntax as the attribute:
+/// generics with a where clause, a visibility and doc comments before `impl`.
+/// Only one widget can be declared with this macro per module.
 #[macro_export]
 macro_rules! relm_widget {
     ($($tts:tt)*) => {
-        mod __relm_gen_private {
-            use super::*;
-
-            #[derive(Widget)]
-            struct __RelmPrivateWidget {
-                widget: impl_widget! {
-                    $($tts)*
-                }
+        #[allow(dead_code)]
+        #[derive(Widget)]
+        struct __RelmPrivateWidget {
+            widget: impl_widget! {
+                $($tts)*
             }
         }
-
-        use_impl_self_type!($($tts)*);
-    };
-}
-
-#[doc(hidden)]
-#[macro_export]
-macro_rules! use_impl_self_type {
-    (impl Widget for $self_type:ident { $($tts:tt)* }) => {
-        pub use __relm_gen_private::$self_type;
     };
 }
 
@@ -289,12 +302,16 @@ impl<MSG: Clone + DisplayVariant + Send + 'static> Relm<MSG> {
     }
 
     /// Spawn a future in the tokio event loop.
+    /// The future is cancelled when the widget is removed from its container.
     ///
     /// ## Warning
     /// This function **must** be executed of the tokio thread, i.e. in the
     /// [`subscriptions()`](trait.Widget.html#method.subscriptions) or
     /// [`update_command()`](trait.Widget.html#method.update_command) methods.
     pub fn exec<FUTURE: Future<Item=(), Error=()> + 'static>(&self, future: FUTURE) {
+        let future = future.select(self.stream.closed())
+            .map(|_| ())
+            .map_err(|_| ());
         self.handle.spawn(future);
     }
 
@@ -313,12 +330,61 @@ impl<MSG: Clone + DisplayVariant + Send + 'static> Relm<MSG> {
 /// Handle to the tokio event loop, to be used from the GTK+ thread.
 #[derive(Clone)]
 pub struct RemoteRelm<WIDGET: Widget> {
+    // The model fields updated by the bound widgets since the last update().
+    bound_fields: Arc<Mutex<Vec<&'static str>>>,
     model: Arc<Mutex<WIDGET::Model>>,
     remote: Remote,
     stream: EventStream<WIDGET::Msg>,
+    // Whether the widgets are being changed from the model by view() or update().
+    updating: Arc<AtomicBool>,
 }
 
 impl<WIDGET: Widget> RemoteRelm<WIDGET> {
+    /// Create a future with `create_future` and spawn it on the tokio thread, to send the message
+    /// `success_callback` or `failure_callback` with its result.
+    /// This is [`Relm::connect_exec()`](struct.Relm.html#method.connect_exec) for the GTK+ thread: it is
+    /// used by `clicked => async { future } -> (Msg, FailMsg)` in the `view!` macro.
+    pub fn connect_exec<CREATE, CALLBACK, FAILCALLBACK, STREAM, TOSTREAM>(&self, create_future: CREATE,
+            success_callback: CALLBACK, failure_callback: FAILCALLBACK)
+        where CREATE: FnOnce(&Handle) -> TOSTREAM + Send + 'static,
+              CALLBACK: Fn(STREAM::Item) -> WIDGET::Msg + Send + 'static,
+              FAILCALLBACK: Fn(STREAM::Error) -> WIDGET::Msg + Send + 'static,
+              STREAM: Stream + 'static,
+              TOSTREAM: ToStream<STREAM, Item=STREAM::Item, Error=STREAM::Error> + 'static,
+              WIDGET::Msg: Clone + DisplayVariant + Send + 'static,
+    {
+        let stream = self.stream.clone();
+        self.remote.spawn(move |handle| {
+            let relm = Relm {
+                handle: handle.clone(),
+                stream: stream,
+            };
+            relm.connect_exec(create_future(handle), success_callback, failure_callback);
+            Ok(())
+        });
+    }
+
+    /// This function is the same as [`RemoteRelm::connect_exec()`](struct.RemoteRelm.html#method.connect_exec)
+    /// except it does not take a `failure_callback`; hence, it ignores the errors.
+    pub fn connect_exec_ignore_err<CREATE, CALLBACK, STREAM, TOSTREAM>(&self, create_future: CREATE,
+            success_callback: CALLBACK)
+        where CREATE: FnOnce(&Handle) -> TOSTREAM + Send + 'static,
+              CALLBACK: Fn(STREAM::Item) -> WIDGET::Msg + Send + 'static,
+              STREAM: Stream + 'static,
+              TOSTREAM: ToStream<STREAM, Item=STREAM::Item, Error=STREAM::Error> + 'static,
+              WIDGET::Msg: Clone + DisplayVariant + Send + 'static,
+    {
+        let stream = self.stream.clone();
+        self.remote.spawn(move |handle| {
+            let relm = Relm {
+                handle: handle.clone(),
+                stream: stream,
+            };
+            relm.connect_exec_ignore_err(create_future(handle), success_callback);
+            Ok(())
+        });
+    }
+
     /// Get the shared model.
     pub fn model(&self) -> &Arc<Mutex<WIDGET::Model>> {
         &self.model
@@ -329,6 +395,34 @@ impl<WIDGET: Widget> RemoteRelm<WIDGET> {
     pub fn stream(&self) -> &EventStream<WIDGET::Msg> {
         &self.stream
     }
+
+    /// Take the model fields updated by the bound widgets since the last call, so that `update()`
+    /// sets the other properties using them.
+    /// This is used internally by the library.
+    pub fn take_bound_fields(&self) -> Vec<&'static str> {
+        let mut bound_fields = self.bound_fields.lock().unwrap();
+        mem::replace(&mut *bound_fields, vec![])
+    }
+
+    /// Update the model field `field` from a widget property bound with `bind` in the `view!` macro.
+    /// Nothing is done when the property was changed by `view()` or `update()`, since its value
+    /// then comes from the model: this avoids updating the model in a loop.
+    /// Return whether the model was updated.
+    /// This is used internally by the library.
+    pub fn update_bound_model<CALLBACK: FnOnce(&mut WIDGET::Model)>(&self, field: &'static str, callback: CALLBACK)
+        -> bool
+    {
+        if self.updating.load(Ordering::SeqCst) {
+            return false;
+        }
+        let mut model = self.model.lock().unwrap();
+        callback(&mut *model);
+        let mut bound_fields = self.bound_fields.lock().unwrap();
+        if !bound_fields.contains(&field) {
+            bound_fields.push(field);
+        }
+        true
+    }
 }
 
 fn create_widget_test<WIDGET>(remote: &Remote, model_param: WIDGET::ModelParam) -> Component<WIDGET>
@@ -355,23 +449,40 @@ pub fn create_component<CHILDWIDGET, WIDGET>(relm: &RemoteRelm<WIDGET>,
     Component::new(component)
 }
 
+/// Function called with the message after it was processed by `Widget::update()`.
+type UpdateHook<WIDGET> = Box<FnMut(&WIDGET, <WIDGET as Widget>::Msg, &<WIDGET as Widget>::Model)>;
+
 fn create_widget<WIDGET>(remote: &Remote, model_param: WIDGET::ModelParam) -> Comp<WIDGET>
     where WIDGET: Widget + 'static,
           WIDGET::Msg: Clone + DisplayVariant + 'static,
+{
+    create_widget_with_hook(remote, model_param, None)
+}
+
+fn create_widget_with_hook<WIDGET>(remote: &Remote, model_param: WIDGET::ModelParam,
+        mut hook: Option<UpdateHook<WIDGET>>) -> Comp<WIDGET>
+    where WIDGET: Widget + 'static,
+          WIDGET::Msg: Clone + DisplayVariant + 'static,
 {
     let (sender, mut receiver) = channel();
     let stream = EventStream::new(Arc::new(Mutex::new(sender)));
 
+    let updating = Arc::new(AtomicBool::new(false));
     let (widget, model) = {
         let model = Arc::new(Mutex::new(WIDGET::model(model_param)));
         let relm = RemoteRelm {
+            bound_fields: Arc::new(Mutex::new(vec![])),
             model: model,
             remote: remote.clone(),
             stream: stream.clone(),
+            updating: updating.clone(),
         };
         let view = {
             let model_guard = relm.model.lock().unwrap();
-            WIDGET::view(&relm, &*model_guard)
+            updating.store(true, Ordering::SeqCst);
+            let view = WIDGET::view(&relm, &*model_guard);
+            updating.store(false, Ordering::SeqCst);
+            view
         };
         (view, relm.model)
     };
@@ -384,13 +495,23 @@ fn create_widget<WIDGET>(remote: &Remote, model_param: WIDGET::ModelParam) -> Co
         receiver.connect_recv(move || {
             if let Some(event) = stream.pop_ui_events() {
                 let mut model = model.lock().unwrap();
-                update_widget(&mut widget, event, &mut *model);
+                updating.store(true, Ordering::SeqCst);
+                if let Some(ref mut hook) = hook {
+                    let msg = event.clone();
+                    update_widget(&mut widget, event, &mut *model);
+                    hook(&widget, msg, &*model);
+                }
+                else {
+                    update_widget(&mut widget, event, &mut *model);
+                }
+                updating.store(false, Ordering::SeqCst);
             }
             Continue(true)
         });
     }
 
     Comp {
+        children: Rc::new(RefCell::new(vec![])),
         model: model,
         _receiver: Arc::new(receiver),
         stream: stream,
@@ -567,6 +688,25 @@ pub fn run<WIDGET>(model_param: WIDGET::ModelParam) -> Result<(), ()>
     Ok(())
 }
 
+/// Create the specified relm `Widget` and run the main event loops until every window is closed.
+///
+/// Unlike [`run()`](fn.run.html), the main loop is exited without calling `gtk::main_quit()`: when
+/// the window of the `Widget` is closed and no other window opened by
+/// [`open_window()`](fn.open_window.html) remains.
+pub fn run_windows<WIDGET>(model_param: WIDGET::ModelParam) -> Result<(), ()>
+    where WIDGET: Widget + 'static,
+          WIDGET::Model: Clone + Send,
+          WIDGET::Msg: Send,
+{
+    let component = init::<WIDGET>(model_param)?;
+    quit_on_last_window();
+    if let Some(window) = root_window(component.widget()) {
+        let _ = track(&window, None);
+    }
+    gtk::main();
+    Ok(())
+}
+
 fn update_widget<WIDGET>(widget: &mut WIDGET, event: WIDGET::Msg, model: &mut WIDGET::Model)
     where WIDGET: Widget,
 {
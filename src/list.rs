@@ -0,0 +1,199 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::mem;
+use std::rc::Rc;
+
+use gtk::{self, ContainerExt, IsA};
+
+use component::Component;
+use container::{Position, move_child};
+use widget::Widget;
+
+/// Trait to implement for a relm `Widget` created by a `for` loop in the `view!` macro.
+pub trait ListItem: Widget {
+    /// Get the message to send to the widget when the data of its item changes.
+    /// When `None` is returned, the widget is created again with the new model parameter.
+    fn changed(_model_param: Self::ModelParam) -> Option<Self::Msg> {
+        None
+    }
+}
+
+struct ListState<WIDGET: Widget>
+    where WIDGET::Model: Clone
+{
+    children: Vec<(WIDGET::ModelParam, Component<WIDGET>)>,
+    // The keys of the children, as a Vec<KEY>: the type of the keys is only known in update().
+    keys: Box<Any>,
+}
+
+/// Children created by a `for` loop in the `view!` macro, identified by their key.
+///
+/// The clones of a list share its children, so that every copy of the widget sees the same ones.
+pub struct KeyedList<WIDGET: Widget>
+    where WIDGET::Model: Clone
+{
+    state: Rc<RefCell<ListState<WIDGET>>>,
+}
+
+impl<WIDGET: Widget> Clone for KeyedList<WIDGET>
+    where WIDGET::Model: Clone,
+{
+    fn clone(&self) -> Self {
+        KeyedList {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<WIDGET: ListItem> KeyedList<WIDGET>
+    where WIDGET::Model: Clone,
+          WIDGET::ModelParam: Clone + PartialEq,
+          WIDGET::Msg: Clone + 'static,
+          WIDGET::Root: IsA<gtk::Widget>,
+{
+    /// Create an empty list.
+    pub fn new() -> Self {
+        KeyedList {
+            state: Rc::new(RefCell::new(ListState {
+                children: vec![],
+                keys: Box::new(()),
+            })),
+        }
+    }
+
+    /// Get the number of children.
+    pub fn len(&self) -> usize {
+        self.state.borrow().children.len()
+    }
+
+    /// Update the children of `container` so that there is one child per item, in the same order,
+    /// starting at index `offset` among the children of the container.
+    /// Children are matched with the items by key: new keys create a widget with `create`, which adds
+    /// it to the container, missing keys remove their widget and changed model parameters are sent to
+    /// the widget with `ListItem::changed()`.
+    /// When many items have the same key, they are matched with the children of this key in order.
+    pub fn update<'a, CONTAINER, ITEM, ITEMS, KEY, KEYFN, PARAMFN, CREATE>(&self, container: &CONTAINER,
+            offset: i32, items: ITEMS, get_key: KEYFN, get_model_param: PARAMFN, mut create: CREATE)
+        where CONTAINER: IsA<gtk::Container>,
+              ITEM: 'a,
+              ITEMS: IntoIterator<Item=&'a ITEM>,
+              KEY: Eq + Hash + 'static,
+              KEYFN: Fn(&ITEM) -> KEY,
+              PARAMFN: Fn(&ITEM) -> WIDGET::ModelParam,
+              CREATE: FnMut(&ITEM) -> Component<WIDGET>,
+    {
+        // The state is not borrowed while the widgets are created and receive their messages.
+        let (old_keys, old_children) = {
+            let mut state = self.state.borrow_mut();
+            (mem::replace(&mut state.keys, Box::new(())), mem::replace(&mut state.children, vec![]))
+        };
+        let items: Vec<_> = items.into_iter().collect();
+        let keys: Vec<KEY> = items.iter().map(|item| get_key(*item)).collect();
+        let no_keys = vec![];
+        let old_keys = old_keys.downcast_ref::<Vec<KEY>>().unwrap_or(&no_keys);
+        let matches = match_keys(old_keys, &keys);
+        let mut old_children: Vec<_> = old_children.into_iter().map(Some).collect();
+        let mut children = vec![];
+        for (position, (item, old_index)) in items.into_iter().zip(matches).enumerate() {
+            let model_param = get_model_param(item);
+            let component =
+                match old_index.and_then(|index| old_children[index].take()) {
+                    Some((old_model_param, component)) => {
+                        if old_model_param == model_param {
+                            component
+                        }
+                        else if let Some(msg) = WIDGET::changed(model_param.clone()) {
+                            component.stream().emit(msg);
+                            component
+                        }
+                        else {
+                            remove(container, component);
+                            create(item)
+                        }
+                    },
+                    None => create(item),
+                };
+            move_child(component.widget().root(), Position::Index(offset + position as i32));
+            children.push((model_param, component));
+        }
+        for (_, component) in old_children.into_iter().filter_map(|child| child) {
+            remove(container, component);
+        }
+        let mut state = self.state.borrow_mut();
+        state.children = children;
+        state.keys = Box::new(keys);
+    }
+}
+
+fn remove<CONTAINER: IsA<gtk::Container>, WIDGET: Widget>(container: &CONTAINER, component: Component<WIDGET>)
+    where WIDGET::Model: Clone,
+          WIDGET::Root: IsA<gtk::Widget>,
+{
+    container.remove(component.widget().root());
+    component.stop();
+}
+
+/// Get the index of the old child to reuse for each new key, if any.
+/// The old children with the same key are reused in order.
+fn match_keys<KEY: Eq + Hash>(old_keys: &[KEY], keys: &[KEY]) -> Vec<Option<usize>> {
+    let mut old_indices: HashMap<&KEY, Vec<usize>> = HashMap::new();
+    for (index, key) in old_keys.iter().enumerate().rev() {
+        old_indices.entry(key).or_insert_with(Vec::new).push(index);
+    }
+    keys.iter()
+        .map(|key| old_indices.get_mut(key).and_then(|indices| indices.pop()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::match_keys;
+
+    #[test]
+    fn same_keys() {
+        assert_eq!(match_keys(&[1, 2, 3], &[1, 2, 3]), vec![Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn reorder() {
+        assert_eq!(match_keys(&[1, 2, 3], &[3, 1, 2]), vec![Some(2), Some(0), Some(1)]);
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        assert_eq!(match_keys(&[1, 2, 3], &[4, 1, 3]), vec![None, Some(0), Some(2)]);
+        assert_eq!(match_keys::<i32>(&[], &[1, 2]), vec![None, None]);
+        assert_eq!(match_keys(&[1, 2], &[]), vec![]);
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        // Every old child is reused once, so none of them stays in the container.
+        assert_eq!(match_keys(&[1, 1, 2], &[2, 1, 1]), vec![Some(2), Some(0), Some(1)]);
+        assert_eq!(match_keys(&[1, 1], &[1]), vec![Some(0)]);
+        assert_eq!(match_keys(&[1], &[1, 1]), vec![Some(0), None]);
+    }
+}
@@ -0,0 +1,75 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use glib::Cast;
+use glib::object::Downcast;
+use gtk::{self, Inhibit, IsA, WidgetExt};
+
+/// Attach a `gtk::Menu` to `widget`.
+///
+/// The menu is used as the submenu of a `gtk::MenuItem` and as the popup of a `gtk::MenuButton`.
+/// For any other widget, the menu is shown as a context menu when the widget is right-clicked.
+///
+/// ## Note
+/// In the later case, the widget must receive the button press events, so a widget without its
+/// own window (like a `gtk::Label`) should be wrapped in a `gtk::EventBox`.
+pub fn attach_menu<W: Clone + IsA<gtk::Widget>>(widget: &W, menu: &gtk::Menu) {
+    let widget: gtk::Widget = widget.clone().upcast();
+    let widget =
+        match widget.downcast::<gtk::MenuItem>() {
+            Ok(item) => {
+                item.set_submenu(Some(menu));
+                return;
+            },
+            Err(widget) => widget,
+        };
+    let widget =
+        match widget.downcast::<gtk::MenuButton>() {
+            Ok(button) => {
+                button.set_popup(Some(menu));
+                return;
+            },
+            Err(widget) => widget,
+        };
+    menu.attach_to_widget(&widget, None);
+    let menu = menu.clone();
+    let _ = widget.connect_button_press_event(move |_, event| {
+        if event.get_button() == 3 {
+            menu.popup_easy(event.get_button(), event.get_time());
+            Inhibit(true)
+        }
+        else {
+            Inhibit(false)
+        }
+    });
+}
+
+/// Attach a `gtk::Popover` to `widget`.
+///
+/// The popover is used as the popover of a `gtk::MenuButton` and is shown next to any other
+/// widget when it becomes visible.
+pub fn attach_popover<W: Clone + IsA<gtk::Widget>>(widget: &W, popover: &gtk::Popover) {
+    let widget: gtk::Widget = widget.clone().upcast();
+    match widget.downcast::<gtk::MenuButton>() {
+        Ok(button) => button.set_popover(Some(popover)),
+        Err(widget) => popover.set_relative_to(Some(&widget)),
+    }
+}
@@ -0,0 +1,133 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+
+// Used by the code generated by the #[widget] attribute to only call a setter in update() when its
+// argument is different from the one given the last time.
+// Only the owned values are compared: a reference could only be saved by cloning the value it points
+// to, which would be done for every update.
+// The comparison is chosen with the method resolution: (&Setter(&value)).changed(&last) uses
+// ChangedValue when the value implements SetterValue, and ChangedAlways otherwise.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::{
+    Align,
+    ArrowType,
+    ButtonBoxStyle,
+    Justification,
+    Orientation,
+    PackType,
+    PolicyType,
+    PositionType,
+    ReliefStyle,
+    SelectionMode,
+    ShadowType,
+    WindowPosition,
+    WindowType,
+};
+
+/// A setter argument which is compared with the last one.
+pub trait SetterValue: Clone + PartialEq + 'static {
+}
+
+macro_rules! impl_setter_value {
+    ($($typ:ty),*) => {
+        $(
+            impl SetterValue for $typ {
+            }
+        )*
+    };
+}
+
+impl_setter_value!(bool, char, f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, String);
+
+impl_setter_value!(Align, ArrowType, ButtonBoxStyle, Justification, Orientation, PackType, PolicyType,
+    PositionType, ReliefStyle, SelectionMode, ShadowType, WindowPosition, WindowType);
+
+impl<T: SetterValue> SetterValue for Option<T> {
+}
+
+/// The last argument given to a setter, shared by the copies of the widget.
+#[derive(Clone, Default)]
+pub struct LastValue {
+    value: Rc<RefCell<Option<Box<Any>>>>,
+}
+
+/// A setter argument to check with `changed()`.
+pub struct Setter<'a, T: 'a>(pub &'a T);
+
+/// Check whether the argument is different from the last one, and save it.
+pub trait ChangedValue {
+    fn changed(&self, last: &LastValue) -> bool;
+}
+
+impl<'a, T: SetterValue> ChangedValue for Setter<'a, T> {
+    fn changed(&self, last: &LastValue) -> bool {
+        let mut last = last.value.borrow_mut();
+        if let Some(ref value) = *last {
+            if value.downcast_ref::<T>() == Some(self.0) {
+                return false;
+            }
+        }
+        *last = Some(Box::new(self.0.clone()));
+        true
+    }
+}
+
+/// The arguments which cannot be compared are always considered as changed.
+pub trait ChangedAlways {
+    fn changed(&self, _last: &LastValue) -> bool {
+        true
+    }
+}
+
+impl<'a, 'b, T> ChangedAlways for &'b Setter<'a, T> {
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChangedAlways, ChangedValue, LastValue, Setter};
+
+    #[test]
+    fn owned_values_are_compared() {
+        let last = LastValue::default();
+        assert!((&Setter(&1)).changed(&last));
+        assert!(!(&Setter(&1)).changed(&last));
+        assert!((&Setter(&2)).changed(&last));
+        assert!((&Setter(&Some("a".to_string()))).changed(&last));
+        assert!(!(&Setter(&Some("a".to_string()))).changed(&last));
+        // The copies of the widget share the last value.
+        assert!(!(&Setter(&Some("a".to_string()))).changed(&last.clone()));
+    }
+
+    #[test]
+    fn references_are_always_changed() {
+        let last = LastValue::default();
+        let text = "text".to_string();
+        assert!((&Setter(&&text)).changed(&last));
+        assert!((&Setter(&&text)).changed(&last));
+        assert!((&Setter(&"text")).changed(&last));
+        assert!((&Setter(&"text")).changed(&last));
+    }
+}
@@ -21,6 +21,7 @@
 
 use gtk::{self, IsA, Object};
 
+use container::Placement;
 use super::{DisplayVariant, Relm, RemoteRelm, run};
 
 /// Trait to implement to manage widget's events.
@@ -59,8 +60,17 @@ pub trait Widget
         None
     }
 
-    // TODO: ajouter une méthode param() pour déterminer des paramètres qui seront pris en compte à
-    // l’ajout du widget.
+    /// Get where the widget is added in a relm container, computed from its model parameter.
+    /// This is useful to choose the containing widget or the position of the widget from a value
+    /// known when it is created, like a cell of a `gtk::Grid`: the placement is given to
+    /// [`Container::add_widget_at()`](trait.Container.html#method.add_widget_at).
+    /// By default, the widget is added at the end of the container whose id is `parent_id()`.
+    fn placement(_model_param: &Self::ModelParam) -> Placement {
+        Placement {
+            parent_id: Self::parent_id().map(str::to_string),
+            position: None,
+        }
+    }
 
     /// Get the root widget of the view.e. the root widget of the view.
     fn root(&self) -> &Self::Root;
@@ -0,0 +1,184 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use glib::Cast;
+use glib::object::Downcast;
+use gtk::{self, ContainerExt, WidgetExt, WindowType};
+
+use component::Component;
+use super::{DisplayVariant, EventStream, RemoteRelm, create_widget, init_component};
+use widget::Widget;
+
+thread_local! {
+    static WINDOWS: RefCell<Windows> = RefCell::new(Windows::new());
+}
+
+/// Identifier of a top-level window tracked by relm.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct WindowId(usize);
+
+struct TrackedWindow {
+    // The component is None for the root widget, since it is owned by the run() functions.
+    component: Option<Box<Any>>,
+    window: gtk::Window,
+}
+
+struct Windows {
+    application: Option<gtk::Application>,
+    next_id: usize,
+    // Whether the main loop is exited when the last window is closed, which is opt-in.
+    quit_on_last_window: bool,
+    windows: HashMap<WindowId, TrackedWindow>,
+}
+
+impl Windows {
+    fn new() -> Self {
+        Windows {
+            application: None,
+            next_id: 0,
+            quit_on_last_window: false,
+            windows: HashMap::new(),
+        }
+    }
+}
+
+/// Add the next windows to the `gtk::Application`, which quits by itself when its last window
+/// is closed.
+pub fn set_application(application: &gtk::Application) {
+    WINDOWS.with(|windows| {
+        windows.borrow_mut().application = Some(application.clone());
+    });
+}
+
+/// Exit the main loop when the last tracked window is closed.
+pub fn quit_on_last_window() {
+    WINDOWS.with(|windows| {
+        windows.borrow_mut().quit_on_last_window = true;
+    });
+}
+
+/// Get the root of the widget if it is a `gtk::Window`.
+pub fn root_window<WIDGET: Widget>(widget: &WIDGET) -> Option<gtk::Window> {
+    let root: gtk::Widget = widget.root().clone().upcast();
+    root.downcast::<gtk::Window>().ok()
+}
+
+/// Get the window of the widget, creating a new one around its root if needed.
+pub fn toplevel_window<WIDGET: Widget>(widget: &WIDGET) -> gtk::Window {
+    root_window(widget).unwrap_or_else(|| {
+        let window = gtk::Window::new(WindowType::Toplevel);
+        window.add(widget.root());
+        window
+    })
+}
+
+/// Track the window so that the main loop can be exited when the last window is closed.
+pub fn track(window: &gtk::Window, component: Option<Box<Any>>) -> WindowId {
+    let id = WINDOWS.with(|windows| {
+        let mut windows = windows.borrow_mut();
+        let id = WindowId(windows.next_id);
+        windows.next_id += 1;
+        if let Some(ref application) = windows.application {
+            application.add_window(window);
+        }
+        windows.windows.insert(id, TrackedWindow {
+            component: component,
+            window: window.clone(),
+        });
+        id
+    });
+    let _ = window.connect_destroy(move |_| untrack(id));
+    id
+}
+
+fn untrack(id: WindowId) {
+    let (window, quit) = WINDOWS.with(|windows| {
+        let mut windows = windows.borrow_mut();
+        let window = windows.windows.remove(&id);
+        let quit = window.is_some() && windows.windows.is_empty() && windows.quit_on_last_window;
+        (window, quit)
+    });
+    // Drop the component outside of the borrow since it can close other windows.
+    drop(window);
+    if quit && gtk::main_level() > 0 {
+        gtk::main_quit();
+    }
+}
+
+/// Close the top-level window `id`.
+pub fn close_window(id: WindowId) {
+    let window = WINDOWS.with(|windows| {
+        windows.borrow().windows.get(&id).map(|window| window.window.clone())
+    });
+    if let Some(window) = window {
+        window.destroy();
+    }
+}
+
+/// Create a relm widget in a new top-level window.
+///
+/// The window is kept alive until it is closed, at which point the message returned by
+/// `on_close` is sent to the widget that opened it.
+/// If the root of the widget is not a `gtk::Window`, it is added to a new one.
+/// The main loop is exited when the last window is closed only if it was started by
+/// [`run_windows()`](fn.run_windows.html).
+pub fn open_window<CHILDWIDGET, WIDGET, CALLBACK>(relm: &RemoteRelm<WIDGET>, model_param: CHILDWIDGET::ModelParam,
+        on_close: CALLBACK) -> WindowId
+    where CHILDWIDGET: Widget + 'static,
+          CHILDWIDGET::Model: Clone + Send,
+          CHILDWIDGET::Msg: Clone + DisplayVariant + Send + 'static,
+          WIDGET: Widget,
+          WIDGET::Msg: 'static,
+          CALLBACK: Fn(WindowId) -> WIDGET::Msg + 'static,
+{
+    let component = create_widget::<CHILDWIDGET>(&relm.remote, model_param);
+    let window = toplevel_window(&component.widget);
+    init_component::<CHILDWIDGET>(&component, &relm.remote);
+    let id = track(&window, Some(Box::new(Component::new(component))));
+    let stream = relm.stream().clone();
+    let _ = window.connect_destroy(move |_| stream.emit(on_close(id)));
+    window.show();
+    id
+}
+
+/// Get the number of open top-level windows.
+pub fn window_count() -> usize {
+    WINDOWS.with(|windows| windows.borrow().windows.len())
+}
+
+/// Get the event stream of the widget in the window `id`, to send it messages.
+///
+/// Returns `None` if the window is closed or does not contain a `CHILDWIDGET`.
+pub fn window_stream<CHILDWIDGET>(id: WindowId) -> Option<EventStream<CHILDWIDGET::Msg>>
+    where CHILDWIDGET: Widget + 'static,
+          CHILDWIDGET::Model: Clone,
+{
+    WINDOWS.with(|windows| {
+        windows.borrow().windows.get(&id)
+            .and_then(|window| window.component.as_ref())
+            .and_then(|component| component.downcast_ref::<Component<CHILDWIDGET>>())
+            .map(|component| component.stream().clone())
+    })
+}